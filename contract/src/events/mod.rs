@@ -0,0 +1,180 @@
+// Events emitted by the contract, following the NEP-297 event log format.
+
+use std::fmt;
+use crate::*;
+
+pub const FUNDRAISERS_STANDARD_NAME: &str = "u_fundraisers";
+pub const FUNDRAISERS_EVENTS_VERSION: &str = "1.0.0";
+//token events follow the NEP-171 standard so that wallets and marketplaces understand them
+pub const NFT_STANDARD_NAME: &str = "nep171";
+pub const NFT_EVENTS_VERSION: &str = "1.0.0";
+
+//all the events of the contract. The variant name becomes the "event" field, its content the "data" field
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(tag = "event", content = "data")]
+#[serde(rename_all = "snake_case")]
+#[serde(crate = "near_sdk::serde")]
+#[non_exhaustive]
+pub enum EventLogVariant {
+    FundraiserUpdate(Vec<FundraiserUpdateLog>),
+    FundraiserArchive(Vec<FundraiserArchiveLog>),
+    TokenMetadataUpdate(Vec<TokenMetadataUpdateLog>),
+    NftBurn(Vec<NftBurnLog>),
+    FundraiserPayout(Vec<FundraiserPayoutLog>),
+    TeamMemberAdd(Vec<TeamMemberLog>),
+    TeamMemberUpdate(Vec<TeamMemberLog>),
+    TeamMemberRemove(Vec<TeamMemberLog>),
+    DonationGift(Vec<DonationGiftLog>),
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct EventLog {
+    pub standard: String,
+    pub version: String,
+
+    // `flatten` to not have "event": {<EventLogVariant>} in the JSON, just have the contents of {<EventLogVariant>}.
+    #[serde(flatten)]
+    pub event: EventLogVariant,
+}
+
+impl EventLog {
+    pub fn new(event: EventLogVariant) -> Self {
+        EventLog {
+            standard: FUNDRAISERS_STANDARD_NAME.to_string(),
+            version: FUNDRAISERS_EVENTS_VERSION.to_string(),
+            event,
+        }
+    }
+
+    //event of the NEP-171 standard
+    pub fn nft(event: EventLogVariant) -> Self {
+        EventLog {
+            standard: NFT_STANDARD_NAME.to_string(),
+            version: NFT_EVENTS_VERSION.to_string(),
+            event,
+        }
+    }
+
+    //logs the event so that indexers can pick it up
+    pub fn emit(&self) {
+        env::log_str(&self.to_string());
+    }
+}
+
+impl fmt::Display for EventLog {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_fmt(format_args!(
+            "EVENT_JSON:{}",
+            &near_sdk::serde_json::to_string(self).map_err(|_| fmt::Error)?
+        ))
+    }
+}
+
+//a fundraiser owner posted an update, donors of the fundraiser should be notified
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FundraiserUpdateLog {
+    pub fundraiser_id: FundraiserId,
+    pub update_id: u64,
+    pub author_id: AccountId,
+    pub title: String,
+}
+
+//a fundraiser without donations was removed by its owner or a moderator
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FundraiserArchiveLog {
+    pub fundraiser_id: FundraiserId,
+    pub archived_by: AccountId,
+}
+
+//the owner of a fundraiser changed the metadata of its campaign token
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct TokenMetadataUpdateLog {
+    pub token_id: TokenId,
+    pub fundraiser_id: FundraiserId,
+    // revision holding the replaced metadata
+    pub revision_id: u64,
+    pub updated_by: AccountId,
+}
+
+//part of the funds collected by a fundraiser reached one of its beneficiaries
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FundraiserPayoutLog {
+    pub fundraiser_id: FundraiserId,
+    pub beneficiary_id: AccountId,
+    pub amount: U128,
+}
+
+//someone joined or left the team of a fundraiser, or got different permissions
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct TeamMemberLog {
+    pub fundraiser_id: FundraiserId,
+    pub account_id: AccountId,
+    // permissions of the member, the ones they had before leaving for team_member_remove
+    pub permissions: Vec<TeamPermission>,
+    pub changed_by: AccountId,
+}
+
+//a donation was paid by another account than the one credited for it, or came with a dedication
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct DonationGiftLog {
+    pub fundraiser_id: FundraiserId,
+    // account credited for the donation, it receives the rewards
+    pub donor_id: AccountId,
+    pub paid_by: AccountId,
+    pub amount: U128,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dedication: Option<String>,
+}
+
+//tokens were burned, as defined by NEP-171
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct NftBurnLog {
+    pub owner_id: AccountId,
+    pub token_ids: Vec<TokenId>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub authorized_id: Option<AccountId>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memo: Option<String>,
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::accounts;
+
+    #[test]
+    fn event_log_format_test() {
+        let log = EventLog::new(EventLogVariant::FundraiserUpdate(vec![FundraiserUpdateLog {
+            fundraiser_id: 1,
+            update_id: 0,
+            author_id: accounts(0),
+            title: "Bought the first ambulance".to_string(),
+        }]));
+        assert_eq!(
+            log.to_string(),
+            r#"EVENT_JSON:{"standard":"u_fundraisers","version":"1.0.0","event":"fundraiser_update","data":[{"fundraiser_id":1,"update_id":0,"author_id":"alice","title":"Bought the first ambulance"}]}"#
+        );
+    }
+
+    #[test]
+    fn nft_event_log_format_test() {
+        let log = EventLog::nft(EventLogVariant::NftBurn(vec![NftBurnLog {
+            owner_id: accounts(0),
+            token_ids: vec!["1".to_string()],
+            authorized_id: None,
+            memo: None,
+        }]));
+        assert_eq!(
+            log.to_string(),
+            r#"EVENT_JSON:{"standard":"nep171","version":"1.0.0","event":"nft_burn","data":[{"owner_id":"alice","token_ids":["1"]}]}"#
+        );
+    }
+}
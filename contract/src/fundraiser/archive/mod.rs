@@ -0,0 +1,124 @@
+// Archiving removes fundraisers that never received a donation, like tests and abandoned drafts.
+
+use crate::*;
+
+#[near_bindgen]
+impl Contract {
+    /*
+        removes the fundraiser, its campaign token, its indices and its feeds. Only the fundraiser owner or a
+        moderator can call it. The owner gets back the storage of the campaign token and of the reward tiers,
+        plus the storage prepaid for the reward editions that were never minted.
+    */
+    pub fn archive_fundraiser(&mut self, fundraiser_id: FundraiserId) -> U128 {
+        let fundraiser: Fundraiser = self.fundraisers_by_id.get(&fundraiser_id).expect("Incorrect fundraiser id");
+        let caller_id = env::predecessor_account_id();
+        assert!(
+            caller_id == fundraiser.owner_id || self.is_moderator(&caller_id),
+            "Only the fundraiser owner or a moderator can archive it"
+        );
+        assert!(
+            fundraiser.total_collected.0 == 0
+                && self.fundraisers_donations.get(&fundraiser_id).is_none()
+                && !self.has_pending_donations(fundraiser_id),
+            "Fundraisers with donations can't be archived"
+        );
+
+        //storage paid by the owner
+        let initial_storage_usage = env::storage_usage();
+        if let Some(token_id) = self.campaign_token_by_fundraiser.get(&fundraiser_id) {
+            self.internal_burn(&token_id, Some("Fundraiser archived".to_string()));
+        }
+        let mut unminted_editions: u64 = 0;
+        if let Some(mut tiers) = self.reward_tiers.remove(&fundraiser_id) {
+            unminted_editions = tiers.iter().map(|tier| tier.max_supply - tier.minted).sum();
+            tiers.clear();
+        }
+        let refund = env::storage_byte_cost().as_yoctonear() * u128::from(initial_storage_usage - env::storage_usage())
+            + REWARD_EDITION_STORAGE_COST * u128::from(unminted_editions);
+
+        self.fundraisers_by_id.remove(&fundraiser_id);
+        self.internal_remove_from_sort_indices(fundraiser_id, &fundraiser);
+        self.internal_unindex_fundraiser(fundraiser_id, &fundraiser);
+
+        let mut f_owner_set = self.fundraiser_per_owner.get(&fundraiser.owner_id).unwrap();
+        f_owner_set.remove(&fundraiser_id);
+        if f_owner_set.is_empty() {
+            self.fundraiser_per_owner.remove(&fundraiser.owner_id);
+        } else {
+            self.fundraiser_per_owner.insert(&fundraiser.owner_id, &f_owner_set);
+        }
+
+        self.internal_clear_fundraiser_tokens(fundraiser_id);
+        self.beneficiaries_by_fundraiser.remove(&fundraiser_id);
+        self.internal_clear_team(fundraiser_id);
+        self.vesting_schedules.remove(&fundraiser_id);
+        self.donation_limits.remove(&fundraiser_id);
+        self.cooling_off_periods.remove(&fundraiser_id);
+        if let Some(mut allowlist) = self.fundraiser_allowlists.remove(&fundraiser_id) {
+            allowlist.clear();
+        }
+        self.voucher_keys.remove(&fundraiser_id);
+        self.reported_fundraisers.remove(&fundraiser_id);
+        if let Some(mut reports) = self.fundraiser_reports.remove(&fundraiser_id) {
+            reports.clear();
+        }
+        if let Some(mut updates) = self.fundraiser_updates.remove(&fundraiser_id) {
+            updates.clear();
+        }
+        if let Some(mut comments) = self.fundraiser_comments.remove(&fundraiser_id) {
+            comments.clear();
+        }
+
+        EventLog::new(EventLogVariant::FundraiserArchive(vec![FundraiserArchiveLog {
+            fundraiser_id,
+            archived_by: caller_id,
+        }])).emit();
+
+        if refund > 0 {
+            Promise::new(fundraiser.owner_id).transfer(NearToken::from_yoctonear(refund)).detach();
+        }
+        U128(refund)
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::{accounts, get_logs};
+    use near_sdk::testing_env;
+    use crate::test_helpers::{get_context, init_with_fundraiser};
+
+    #[test]
+    fn archive_fundraiser_test() {
+        let (mut contract, fundraiser_id) = init_with_fundraiser(accounts(1));
+        testing_env!(get_context(accounts(1)).storage_usage(env::storage_usage()).build());
+        let refund = contract.archive_fundraiser(fundraiser_id);
+
+        assert!(refund.0 > 0);
+        assert!(contract.get_fundraiser_by_id(fundraiser_id).is_none());
+        assert_eq!(contract.get_fundraiser_count_by_owner(accounts(1)), U128(0));
+        assert_eq!(contract.get_all_fundraisers(None, None, None, None, None, None).items.len(), 0);
+        assert_eq!(contract.get_all_fundraisers(None, None, None, Some(FundraiserSort::NEWEST), None, None).items.len(), 0);
+        assert_eq!(contract.nft_total_supply(), U128(0));
+        assert!(get_logs().iter().any(|log| log.contains(r#""event":"fundraiser_archive""#)));
+    }
+
+    #[test]
+    #[should_panic(expected = "Fundraisers with donations can't be archived")]
+    fn archive_fundraiser_with_donations_test() {
+        let (mut contract, fundraiser_id) = init_with_fundraiser(accounts(1));
+        testing_env!(get_context(accounts(2)).attached_deposit(NearToken::from_yoctonear(MIN_DONATION)).build());
+        contract.donate_to_fundraiser(fundraiser_id, None, None);
+
+        testing_env!(get_context(accounts(1)).build());
+        contract.archive_fundraiser(fundraiser_id);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the fundraiser owner or a moderator can archive it")]
+    fn archive_fundraiser_not_owner_test() {
+        let (mut contract, fundraiser_id) = init_with_fundraiser(accounts(1));
+        testing_env!(get_context(accounts(2)).build());
+        contract.archive_fundraiser(fundraiser_id);
+    }
+}
@@ -0,0 +1,211 @@
+// Categories and tags of fundraisers together with the secondary indices used for filtered listing.
+
+use crate::*;
+
+const MAX_TAGS_PER_FUNDRAISER: usize = 10;
+const MAX_TAG_LENGTH: usize = 32;
+
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+#[allow(non_camel_case_types)]
+pub enum FundraiserCategory {
+    MEDICAL,
+    MILITARY_SUPPORT,
+    SHELTER,
+    ANIMALS,
+    EDUCATION,
+    HUMANITARIAN,
+    RECONSTRUCTION,
+    OTHER,
+}
+
+//tags are stored trimmed and lowercased so that "Kyiv" and "kyiv " end up in the same index
+pub(crate) fn normalize_tag(tag: &str) -> String {
+    tag.trim().to_lowercase()
+}
+
+pub(crate) fn normalize_tags(tags: Vec<String>) -> Vec<String> {
+    assert!(tags.len() <= MAX_TAGS_PER_FUNDRAISER, "Abort. A fundraiser can have at most 10 tags");
+    let mut normalized: Vec<String> = Vec::with_capacity(tags.len());
+    for tag in tags {
+        let tag = normalize_tag(&tag);
+        assert!(!tag.is_empty(), "Abort. Tag is empty");
+        assert!(tag.len() <= MAX_TAG_LENGTH, "Abort. Tag is longer then 32 characters");
+        if !normalized.contains(&tag) {
+            normalized.push(tag);
+        }
+    }
+    normalized
+}
+
+impl Fundraiser {
+    //whether the fundraiser shows up in listings filtered by the given category, tag and status
+    pub(crate) fn matches_listing_filters(
+        &self,
+        category: &Option<FundraiserCategory>,
+        tag: &Option<String>,
+        status: &Option<FundraiserStatus>,
+    ) -> bool {
+        self.moderation_status == ModerationStatus::VISIBLE
+            && self.visibility == FundraiserVisibility::PUBLIC
+            && category.as_ref().is_none_or(|category| &self.category == category)
+            && tag.as_ref().is_none_or(|tag| self.tags.contains(tag))
+            && status.as_ref().is_none_or(|status| &self.status == status)
+    }
+}
+
+impl Contract {
+    //adds the fundraiser to the category, tag and status indices
+    pub(crate) fn internal_index_fundraiser(&mut self, fundraiser_id: FundraiserId, fundraiser: &Fundraiser) {
+        let mut category_set = self.fundraisers_by_category.get(&fundraiser.category).unwrap_or_else(|| {
+            UnorderedSet::new(
+                StorageKey::FundraisersByCategoryInner { category: fundraiser.category.clone() }
+            )
+        });
+        category_set.insert(&fundraiser_id);
+        self.fundraisers_by_category.insert(&fundraiser.category, &category_set);
+
+        for tag in fundraiser.tags.iter() {
+            let mut tag_set = self.fundraisers_by_tag.get(tag).unwrap_or_else(|| {
+                UnorderedSet::new(
+                    StorageKey::FundraisersByTagInner { tag_hash: env::sha256_array(tag.as_bytes()) }
+                )
+            });
+            tag_set.insert(&fundraiser_id);
+            self.fundraisers_by_tag.insert(tag, &tag_set);
+        }
+
+        let mut status_set = self.fundraisers_by_status.get(&fundraiser.status).unwrap_or_else(|| {
+            UnorderedSet::new(
+                StorageKey::FundraisersByStatusInner { status: fundraiser.status.clone() }
+            )
+        });
+        status_set.insert(&fundraiser_id);
+        self.fundraisers_by_status.insert(&fundraiser.status, &status_set);
+    }
+
+    //removes the fundraiser from the category, tag and status indices
+    pub(crate) fn internal_unindex_fundraiser(&mut self, fundraiser_id: FundraiserId, fundraiser: &Fundraiser) {
+        if let Some(mut category_set) = self.fundraisers_by_category.get(&fundraiser.category) {
+            category_set.remove(&fundraiser_id);
+            self.fundraisers_by_category.insert(&fundraiser.category, &category_set);
+        }
+
+        for tag in fundraiser.tags.iter() {
+            if let Some(mut tag_set) = self.fundraisers_by_tag.get(tag) {
+                tag_set.remove(&fundraiser_id);
+                if tag_set.is_empty() {
+                    self.fundraisers_by_tag.remove(tag);
+                } else {
+                    self.fundraisers_by_tag.insert(tag, &tag_set);
+                }
+            }
+        }
+
+        if let Some(mut status_set) = self.fundraisers_by_status.get(&fundraiser.status) {
+            status_set.remove(&fundraiser_id);
+            self.fundraisers_by_status.insert(&fundraiser.status, &status_set);
+        }
+    }
+
+    /*
+        picks the narrowest index matching the filters (tag, then category, then status) so that
+        listing never has to go through every fundraiser. The index is read lazily from the cursor,
+        a position in it, and the remaining filters are checked until the page is full.
+    */
+    pub(crate) fn internal_filtered_fundraiser_ids(
+        &self,
+        category: &Option<FundraiserCategory>,
+        tag: &Option<String>,
+        status: &Option<FundraiserStatus>,
+        cursor: Option<String>,
+        limit: Option<u64>,
+    ) -> Page<FundraiserId> {
+        let index_set: Option<UnorderedSet<FundraiserId>> = if let Some(tag) = tag {
+            self.fundraisers_by_tag.get(tag)
+        } else if let Some(category) = category {
+            self.fundraisers_by_category.get(category)
+        } else if let Some(status) = status {
+            self.fundraisers_by_status.get(status)
+        } else {
+            None
+        };
+        let index: &Vector<FundraiserId> = match &index_set {
+            Some(index_set) => index_set.as_vector(),
+            None if tag.is_some() || category.is_some() || status.is_some() => return Page { items: vec![], next_cursor: None },
+            None => self.fundraisers_by_id.keys_as_vector(),
+        };
+
+        let mut position: u64 = match cursor {
+            Some(cursor) => cursor.parse().expect("Invalid cursor"),
+            None => 0,
+        };
+        let limit = page_limit(limit);
+        let mut items: Vec<FundraiserId> = vec![];
+        while position < index.len() {
+            let fundraiser_id = index.get(position).unwrap();
+            let matches = self.fundraisers_by_id.get(&fundraiser_id)
+                .is_some_and(|fundraiser| fundraiser.matches_listing_filters(category, tag, status));
+            if matches {
+                //the next page starts at the first match that doesn't fit in this one
+                if items.len() == limit {
+                    return Page { items, next_cursor: Some(position.to_string()) };
+                }
+                items.push(fundraiser_id);
+            }
+            position += 1;
+        }
+        Page { items, next_cursor: None }
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::accounts;
+    use near_sdk::testing_env;
+    use crate::test_helpers::{get_context, empty_token_metadata, new_fundraiser, init, MINT_STORAGE_COST};
+
+    fn add_fundraiser(contract: &mut Contract, category: FundraiserCategory, tags: Vec<&str>) {
+        testing_env!(get_context(accounts(0))
+            .storage_usage(env::storage_usage())
+            .attached_deposit(NearToken::from_yoctonear(MINT_STORAGE_COST))
+            .build());
+        contract.add_new_fundraiser(
+            NewFundraiser {
+                category,
+                tags: tags.into_iter().map(String::from).collect(),
+                ..new_fundraiser("test")
+            },
+            empty_token_metadata(),
+        );
+    }
+
+    #[test]
+    fn normalize_tags_test() {
+        let tags = normalize_tags(vec![" Kyiv".to_string(), "kyiv".to_string(), "Drones".to_string()]);
+        assert_eq!(tags, vec!["kyiv".to_string(), "drones".to_string()]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Abort. Tag is empty")]
+    fn normalize_tags_empty_test() {
+        normalize_tags(vec!["  ".to_string()]);
+    }
+
+    #[test]
+    fn get_all_fundraisers_filtered_test() {
+        let mut contract = init(accounts(0));
+        add_fundraiser(&mut contract, FundraiserCategory::MEDICAL, vec!["kyiv"]);
+        add_fundraiser(&mut contract, FundraiserCategory::ANIMALS, vec!["Kyiv", "cats"]);
+        add_fundraiser(&mut contract, FundraiserCategory::ANIMALS, vec![]);
+
+        assert_eq!(contract.get_all_fundraisers(Some(FundraiserCategory::ANIMALS), None, None, None, None, None).items.len(), 2);
+        assert_eq!(contract.get_all_fundraisers(None, Some("KYIV".to_string()), None, None, None, None).items.len(), 2);
+        assert_eq!(contract.get_all_fundraisers(Some(FundraiserCategory::ANIMALS), Some("kyiv".to_string()), None, None, None, None).items.len(), 1);
+        assert_eq!(contract.get_all_fundraisers(Some(FundraiserCategory::EDUCATION), None, None, None, None, None).items.len(), 0);
+        assert_eq!(contract.get_all_fundraisers(None, None, Some(FundraiserStatus::DRAFT), None, None, None).items.len(), 0);
+        assert_eq!(contract.get_all_fundraisers(None, None, Some(FundraiserStatus::ACTIVE), None, None, None).items.len(), 3);
+    }
+}
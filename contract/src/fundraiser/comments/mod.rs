@@ -0,0 +1,142 @@
+// Messages of support left on fundraisers by any account.
+
+use crate::*;
+
+const MAX_COMMENT_LENGTH: usize = 280;
+
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct FundraiserComment {
+    // position of the comment on the fundraiser, starting from 0
+    pub id: u64,
+    pub author_id: AccountId,
+    pub message: String,
+    // Unix epoch in milliseconds
+    pub created_at: u64,
+    // hidden by a moderator
+    pub hidden: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct JsonFundraiserComment {
+    pub comment: FundraiserComment,
+    // the author has donated to the fundraiser
+    pub verified_donor: bool,
+}
+
+#[near_bindgen]
+impl Contract {
+    //posts a comment on the fundraiser, the author pays for the storage
+    #[payable]
+    pub fn post_comment(&mut self, fundraiser_id: FundraiserId, message: String) -> u64 {
+        let fundraiser: Fundraiser = self.fundraisers_by_id.get(&fundraiser_id).expect("Incorrect fundraiser id");
+        assert!(
+            self.is_allowed_on_fundraiser(fundraiser_id, &fundraiser, &env::predecessor_account_id()),
+            "Only allowlisted accounts can comment on this fundraiser"
+        );
+        assert!(message.trim() != "", "Abort. Message is empty");
+        assert!(message.len() <= MAX_COMMENT_LENGTH, "Abort. Message is longer then 280 characters");
+
+        let initial_storage_usage = env::storage_usage();
+
+        let mut comments = self.fundraiser_comments.get(&fundraiser_id).unwrap_or_else(|| {
+            Vector::new(StorageKey::FundraiserCommentsInner { fundraiser_id })
+        });
+        let comment_id = comments.len();
+        comments.push(&FundraiserComment {
+            id: comment_id,
+            author_id: env::predecessor_account_id(),
+            message,
+            created_at: env::block_timestamp_ms(),
+            hidden: false,
+        });
+        self.fundraiser_comments.insert(&fundraiser_id, &comments);
+
+        refund_deposit(env::storage_usage() - initial_storage_usage);
+
+        comment_id
+    }
+
+    pub fn set_comment_hidden(&mut self, fundraiser_id: FundraiserId, comment_id: u64, hidden: bool) {
+        self.assert_moderator();
+        let mut comments = self.fundraiser_comments.get(&fundraiser_id).expect("Incorrect comment id");
+        let mut comment = comments.get(comment_id).expect("Incorrect comment id");
+        comment.hidden = hidden;
+        comments.replace(comment_id, &comment);
+    }
+
+    //comments of the fundraiser that are not hidden, newest first
+    pub fn get_fundraiser_comments(&self, fundraiser_id: FundraiserId, cursor: Option<String>, limit: Option<u64>) -> Page<JsonFundraiserComment> {
+        let comments = match self.fundraiser_comments.get(&fundraiser_id) {
+            Some(comments) if self.can_view_fundraiser(fundraiser_id, None) => comments,
+            _ => return Page { items: vec![], next_cursor: None },
+        };
+        let page = paginate_newest_first(&comments, cursor, limit, |comment| !comment.hidden);
+
+        let donations = self.fundraisers_donations.get(&fundraiser_id);
+        Page {
+            items: page.items.into_iter()
+                .map(|comment| JsonFundraiserComment {
+                    verified_donor: donations.as_ref().is_some_and(|donations| donations.get(&comment.author_id).is_some()),
+                    comment,
+                })
+                .collect(),
+            next_cursor: page.next_cursor,
+        }
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::accounts;
+    use near_sdk::testing_env;
+    use crate::test_helpers::{get_context, init_with_fundraiser, STORAGE_DEPOSIT};
+
+    fn comment(contract: &mut Contract, author_id: AccountId, fundraiser_id: FundraiserId, message: &str) -> u64 {
+        testing_env!(get_context(author_id)
+            .storage_usage(env::storage_usage())
+            .attached_deposit(NearToken::from_yoctonear(STORAGE_DEPOSIT))
+            .build());
+        contract.post_comment(fundraiser_id, message.to_string())
+    }
+
+    #[test]
+    fn verified_donor_comment_test() {
+        let (mut contract, fundraiser_id) = init_with_fundraiser(accounts(0));
+        testing_env!(get_context(accounts(2)).attached_deposit(NearToken::from_yoctonear(MIN_DONATION)).build());
+        contract.donate_to_fundraiser(fundraiser_id, None, None);
+
+        comment(&mut contract, accounts(2), fundraiser_id, "Glory to Ukraine");
+        comment(&mut contract, accounts(3), fundraiser_id, "Good luck");
+
+        let comments = contract.get_fundraiser_comments(fundraiser_id, None, None).items;
+        assert_eq!(comments.len(), 2);
+        assert_eq!(comments[0].comment.author_id, accounts(3));
+        assert!(!comments[0].verified_donor);
+        assert!(comments[1].verified_donor);
+    }
+
+    #[test]
+    fn hidden_comment_test() {
+        let (mut contract, fundraiser_id) = init_with_fundraiser(accounts(0));
+        let spam_id = comment(&mut contract, accounts(2), fundraiser_id, "spam");
+        comment(&mut contract, accounts(3), fundraiser_id, "Good luck");
+
+        testing_env!(get_context(accounts(0)).build());
+        contract.set_comment_hidden(fundraiser_id, spam_id, true);
+
+        let comments = contract.get_fundraiser_comments(fundraiser_id, None, None).items;
+        assert_eq!(comments.len(), 1);
+        assert_eq!(comments[0].comment.message, "Good luck".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Abort. Message is longer then 280 characters")]
+    fn post_comment_too_long_test() {
+        let (mut contract, fundraiser_id) = init_with_fundraiser(accounts(0));
+        comment(&mut contract, accounts(2), fundraiser_id, &"X".repeat(281));
+    }
+}
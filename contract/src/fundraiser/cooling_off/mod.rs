@@ -0,0 +1,275 @@
+/*
+    Cooling-off period: donations to fundraisers that have one are held as pending donations the donor can
+    cancel until the period ends. Afterwards they are settled and count as regular donations.
+*/
+
+use crate::*;
+
+//longest cooling-off period a fundraiser can have (7 days), in milliseconds
+pub const MAX_COOLING_OFF_PERIOD: u64 = 604_800_000;
+//max number of pending donations settled by one call
+pub const MAX_SETTLEMENTS_PER_CALL: u32 = 50;
+
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct PendingDonation {
+    // account credited for the donation
+    pub donor_id: AccountId,
+    // account the funds came from, the only one that can cancel the donation and the one refunds go to
+    pub paid_by: AccountId,
+    pub amount: U128,
+    // When the donation was made, Unix epoch in milliseconds
+    pub donated_at: u64,
+    // When the donation can't be cancelled anymore, Unix epoch in milliseconds
+    pub cancellable_until: u64,
+    pub dedication: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct JsonPendingDonation {
+    // index to pass to cancel_donation
+    pub donation_index: u64,
+    pub donation: PendingDonation,
+}
+
+#[near_bindgen]
+impl Contract {
+    //sets the cooling-off period of the fundraiser, in milliseconds. 0 turns it off
+    #[payable]
+    pub fn set_cooling_off_period(&mut self, fundraiser_id: FundraiserId, period: u64) {
+        let fundraiser: Fundraiser = self.fundraisers_by_id.get(&fundraiser_id).expect("Incorrect fundraiser id");
+        self.assert_fundraiser_permission(fundraiser_id, &fundraiser, TeamPermission::EDIT_SETTINGS, "set the cooling-off period");
+        assert!(period <= MAX_COOLING_OFF_PERIOD, "Abort. Cooling-off period is longer than 7 days");
+        //pending donations are settled in the order they were made
+        assert!(!self.has_pending_donations(fundraiser_id), "Can't change the cooling-off period while donations are pending");
+
+        let initial_storage_usage = env::storage_usage();
+        if period == 0 {
+            self.cooling_off_periods.remove(&fundraiser_id);
+        } else {
+            self.cooling_off_periods.insert(&fundraiser_id, &period);
+        }
+        refund_deposit(env::storage_usage().saturating_sub(initial_storage_usage));
+    }
+
+    pub fn get_cooling_off_period(&self, fundraiser_id: FundraiserId) -> u64 {
+        self.cooling_off_periods.get(&fundraiser_id).unwrap_or(0)
+    }
+
+    /*
+        refunds a donation that is still in its cooling-off period.
+        The donation is removed from the queue right away so that it doesn't keep storage paid by the contract.
+    */
+    pub fn cancel_donation(&mut self, fundraiser_id: FundraiserId, donation_index: u64) -> U128 {
+        let (_, next_index) = self.pending_donations_range.get(&fundraiser_id).expect("Incorrect donation index");
+        assert!(donation_index < next_index, "Incorrect donation index");
+        let donation = self.pending_donations.get(&(fundraiser_id, donation_index)).expect("Donation was already cancelled or settled");
+        assert_eq!(env::predecessor_account_id(), donation.paid_by, "Only the donor can cancel the donation");
+        assert!(env::block_timestamp_ms() < donation.cancellable_until, "Cooling-off period of the donation is over");
+
+        self.pending_donations.remove(&(fundraiser_id, donation_index));
+        Promise::new(donation.paid_by).transfer(NearToken::from_yoctonear(donation.amount.0)).detach();
+        donation.amount
+    }
+
+    //settles up to `limit` pending donations whose cooling-off period is over, returns how many were settled
+    pub fn settle_donations(&mut self, fundraiser_id: FundraiserId, limit: Option<u32>) -> u32 {
+        let limit = std::cmp::min(limit.unwrap_or(MAX_SETTLEMENTS_PER_CALL), MAX_SETTLEMENTS_PER_CALL);
+        self.internal_settle_donations(fundraiser_id, limit)
+    }
+
+    //donations to the fundraiser made by or on behalf of the donor that can still be cancelled
+    pub fn get_pending_donations(&self, fundraiser_id: FundraiserId, donor_id: AccountId) -> Vec<JsonPendingDonation> {
+        if !self.can_view_fundraiser(fundraiser_id, None) {
+            return vec![];
+        }
+        let (head, next_index) = self.pending_donations_range.get(&fundraiser_id).unwrap_or((0, 0));
+        let now = env::block_timestamp_ms();
+        (head..next_index)
+            .filter_map(|donation_index| {
+                self.pending_donations.get(&(fundraiser_id, donation_index))
+                    .map(|donation| JsonPendingDonation { donation_index, donation })
+            })
+            .filter(|json_donation| {
+                (json_donation.donation.donor_id == donor_id || json_donation.donation.paid_by == donor_id)
+                    && now < json_donation.donation.cancellable_until
+            })
+            .collect()
+    }
+}
+
+impl Contract {
+    pub(crate) fn has_pending_donations(&self, fundraiser_id: FundraiserId) -> bool {
+        self.pending_donations_range.get(&fundraiser_id).is_some_and(|(head, next_index)| head < next_index)
+    }
+
+    /*
+        holds the donation until the end of the cooling-off period of the fundraiser. The queue entry is backed
+        by the donation itself, which is at least MIN_DONATION and covers its storage many times over.
+        Positions are never reused, so the index given to the donor keeps pointing at their donation.
+    */
+    pub(crate) fn internal_queue_donation(
+        &mut self,
+        fundraiser_id: FundraiserId,
+        fundraiser: &Fundraiser,
+        donor_id: &AccountId,
+        paid_by: &AccountId,
+        donation: u128,
+        dedication: Option<String>,
+    ) {
+        assert!(fundraiser.is_accepting_donations(), "Fundraiser is not accepting donations");
+        assert!(
+            self.is_allowed_on_fundraiser(fundraiser_id, fundraiser, donor_id) && self.is_allowed_on_fundraiser(fundraiser_id, fundraiser, paid_by),
+            "Only allowlisted accounts can donate to this fundraiser"
+        );
+
+        let (head, next_index) = self.pending_donations_range.get(&fundraiser_id).unwrap_or((0, 0));
+        let now = env::block_timestamp_ms();
+        self.pending_donations.insert(&(fundraiser_id, next_index), &PendingDonation {
+            donor_id: donor_id.clone(),
+            paid_by: paid_by.clone(),
+            amount: U128(donation),
+            donated_at: now,
+            cancellable_until: now + self.get_cooling_off_period(fundraiser_id),
+            dedication,
+        });
+        self.pending_donations_range.insert(&fundraiser_id, &(head, next_index + 1));
+    }
+
+    /*
+        turns the pending donations whose cooling-off period is over into regular donations, oldest first.
+        What the fundraiser can't accept anymore, because it was closed or reached its limits, goes back to the donor.
+        Every position passed counts against `limit`, cancelled donations included, so a call never runs out of gas.
+    */
+    pub(crate) fn internal_settle_donations(&mut self, fundraiser_id: FundraiserId, limit: u32) -> u32 {
+        let (mut head, next_index) = match self.pending_donations_range.get(&fundraiser_id) {
+            Some(range) => range,
+            None => return 0,
+        };
+        let now = env::block_timestamp_ms();
+        let mut passed: u32 = 0;
+        let mut settled: u32 = 0;
+        while head < next_index && passed < limit {
+            passed += 1;
+            let donation = match self.pending_donations.get(&(fundraiser_id, head)) {
+                Some(donation) => donation,
+                //cancelled
+                None => {
+                    head += 1;
+                    continue;
+                }
+            };
+            if now < donation.cancellable_until {
+                break;
+            }
+            self.pending_donations.remove(&(fundraiser_id, head));
+            head += 1;
+
+            let fundraiser: Fundraiser = self.fundraisers_by_id.get(&fundraiser_id).unwrap();
+            let allowed = self.is_allowed_on_fundraiser(fundraiser_id, &fundraiser, &donation.donor_id)
+                && self.is_allowed_on_fundraiser(fundraiser_id, &fundraiser, &donation.paid_by);
+            let accepted = if fundraiser.is_accepting_donations() && allowed {
+                std::cmp::min(donation.amount.0, self.internal_donation_room(fundraiser_id, &fundraiser, &donation.paid_by))
+            } else {
+                0
+            };
+            if accepted > 0 {
+                self.internal_donate(fundraiser_id, &donation.donor_id, &donation.paid_by, accepted, donation.donated_at, donation.dedication);
+            }
+            if donation.amount.0 > accepted {
+                Promise::new(donation.paid_by).transfer(NearToken::from_yoctonear(donation.amount.0 - accepted)).detach();
+            }
+            settled += 1;
+        }
+
+        self.pending_donations_range.insert(&fundraiser_id, &(head, next_index));
+        settled
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::accounts;
+    use near_sdk::testing_env;
+    use crate::test_helpers::{get_context, init_with_fundraiser, ONE_NEAR, STORAGE_DEPOSIT};
+
+    fn setup() -> (Contract, FundraiserId) {
+        let (mut contract, fundraiser_id) = init_with_fundraiser(accounts(1));
+        testing_env!(get_context(accounts(1))
+            .storage_usage(env::storage_usage())
+            .attached_deposit(NearToken::from_yoctonear(STORAGE_DEPOSIT))
+            .build());
+        contract.set_cooling_off_period(fundraiser_id, 1_000);
+        testing_env!(get_context(accounts(2)).attached_deposit(NearToken::from_yoctonear(2 * ONE_NEAR)).build());
+        contract.donate_to_fundraiser(fundraiser_id, None, None);
+        (contract, fundraiser_id)
+    }
+
+    fn total_collected(contract: &Contract, fundraiser_id: FundraiserId) -> U128 {
+        contract.get_fundraiser_by_id(fundraiser_id).unwrap().fundraiser.total_collected
+    }
+
+    #[test]
+    fn cancel_donation_test() {
+        let (mut contract, fundraiser_id) = setup();
+        let pending = contract.get_pending_donations(fundraiser_id, accounts(2));
+        assert_eq!(pending.len(), 1);
+        assert_eq!(total_collected(&contract, fundraiser_id), U128(0));
+
+        testing_env!(get_context(accounts(2)).block_timestamp(999 * 1_000_000).build());
+        assert_eq!(contract.cancel_donation(fundraiser_id, pending[0].donation_index), U128(2 * ONE_NEAR));
+        assert!(contract.get_pending_donations(fundraiser_id, accounts(2)).is_empty());
+
+        testing_env!(get_context(accounts(1)).block_timestamp(2_000 * 1_000_000).build());
+        assert_eq!(contract.settle_donations(fundraiser_id, None), 0);
+        assert_eq!(total_collected(&contract, fundraiser_id), U128(0));
+    }
+
+    #[test]
+    fn settle_donations_test() {
+        let (mut contract, fundraiser_id) = setup();
+        assert_eq!(contract.get_withdrawable_amount(fundraiser_id), U128(0));
+
+        testing_env!(get_context(accounts(1)).block_timestamp(1_000 * 1_000_000).build());
+        assert_eq!(contract.settle_donations(fundraiser_id, None), 1);
+        assert_eq!(total_collected(&contract, fundraiser_id), U128(2 * ONE_NEAR));
+        assert_eq!(contract.get_withdrawable_amount(fundraiser_id), U128(2 * ONE_NEAR));
+        assert!(!contract.has_pending_donations(fundraiser_id));
+    }
+
+    #[test]
+    fn settle_counts_cancelled_donations_test() {
+        let (mut contract, fundraiser_id) = setup();
+        testing_env!(get_context(accounts(3)).attached_deposit(NearToken::from_yoctonear(ONE_NEAR)).build());
+        contract.donate_to_fundraiser(fundraiser_id, None, None);
+        testing_env!(get_context(accounts(2)).build());
+        contract.cancel_donation(fundraiser_id, 0);
+        assert!(contract.pending_donations.get(&(fundraiser_id, 0)).is_none());
+
+        testing_env!(get_context(accounts(1)).block_timestamp(1_000 * 1_000_000).build());
+        //the cancelled donation uses up the only step
+        assert_eq!(contract.settle_donations(fundraiser_id, Some(1)), 0);
+        assert_eq!(contract.settle_donations(fundraiser_id, Some(1)), 1);
+        assert_eq!(total_collected(&contract, fundraiser_id), U128(ONE_NEAR));
+        assert!(!contract.has_pending_donations(fundraiser_id));
+    }
+
+    #[test]
+    #[should_panic(expected = "Cooling-off period of the donation is over")]
+    fn cancel_donation_after_period_test() {
+        let (mut contract, fundraiser_id) = setup();
+        testing_env!(get_context(accounts(2)).block_timestamp(1_000 * 1_000_000).build());
+        contract.cancel_donation(fundraiser_id, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the donor can cancel the donation")]
+    fn cancel_donation_not_donor_test() {
+        let (mut contract, fundraiser_id) = setup();
+        testing_env!(get_context(accounts(3)).build());
+        contract.cancel_donation(fundraiser_id, 0);
+    }
+}
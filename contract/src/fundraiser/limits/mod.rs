@@ -0,0 +1,165 @@
+// Limits on the donations a fundraiser accepts: a minimum donation, a max per donor and a hard cap.
+
+use std::cmp;
+use crate::*;
+
+/*
+    smallest donation accepted by any fundraiser (0.05 NEAR). Donations take storage paid by the contract,
+    a donor's first donation to a fundraiser up to 2 kB, so the minimum must stay well above its cost.
+*/
+pub const MIN_DONATION: u128 = 50_000_000_000_000_000_000_000;
+
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Debug, Clone, PartialEq, Default)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct DonationLimits {
+    // raises MIN_DONATION for the fundraiser
+    pub min_donation: Option<U128>,
+    // most a single account can pay in total, donations on behalf of others included
+    pub max_per_donor: Option<U128>,
+    // most the fundraiser can collect in total, matched donations included
+    pub hard_cap: Option<U128>,
+}
+
+#[near_bindgen]
+impl Contract {
+    //replaces the donation limits of the fundraiser, the caller pays for the storage
+    #[payable]
+    pub fn set_donation_limits(&mut self, fundraiser_id: FundraiserId, limits: DonationLimits) {
+        let fundraiser: Fundraiser = self.fundraisers_by_id.get(&fundraiser_id).expect("Incorrect fundraiser id");
+        self.assert_fundraiser_permission(fundraiser_id, &fundraiser, TeamPermission::EDIT_SETTINGS, "set the donation limits");
+        let min_donation = cmp::max(limits.min_donation.map_or(0, |min_donation| min_donation.0), MIN_DONATION);
+        if let Some(max_per_donor) = limits.max_per_donor {
+            assert!(max_per_donor.0 >= min_donation, "Abort. Max per donor is below the min donation");
+        }
+        if let Some(hard_cap) = limits.hard_cap {
+            assert!(hard_cap.0 >= min_donation, "Abort. Hard cap is below the min donation");
+        }
+
+        let initial_storage_usage = env::storage_usage();
+        self.donation_limits.insert(&fundraiser_id, &limits);
+        refund_deposit(env::storage_usage().saturating_sub(initial_storage_usage));
+    }
+
+    pub fn get_donation_limits(&self, fundraiser_id: FundraiserId) -> DonationLimits {
+        self.donation_limits.get(&fundraiser_id).unwrap_or_default()
+    }
+}
+
+impl Contract {
+    pub(crate) fn internal_min_donation(&self, fundraiser_id: FundraiserId) -> u128 {
+        let limits = self.get_donation_limits(fundraiser_id);
+        cmp::max(limits.min_donation.map_or(0, |min_donation| min_donation.0), MIN_DONATION)
+    }
+
+    //what the fundraiser can still collect before reaching its hard cap
+    pub(crate) fn internal_hard_cap_room(&self, fundraiser_id: FundraiserId, fundraiser: &Fundraiser) -> u128 {
+        self.get_donation_limits(fundraiser_id).hard_cap
+            .map_or(u128::MAX, |hard_cap| hard_cap.0.saturating_sub(fundraiser.total_collected.0))
+    }
+
+    //most the paying account can still give to the fundraiser
+    pub(crate) fn internal_donation_room(&self, fundraiser_id: FundraiserId, fundraiser: &Fundraiser, paid_by: &AccountId) -> u128 {
+        let donor_room = self.get_donation_limits(fundraiser_id).max_per_donor
+            .map_or(u128::MAX, |max_per_donor| max_per_donor.0.saturating_sub(self.internal_paid_total(fundraiser_id, paid_by)));
+        cmp::min(donor_room, self.internal_hard_cap_room(fundraiser_id, fundraiser))
+    }
+
+    //part of `amount` the fundraiser accepts from the paying account, panics if it can't accept anything
+    pub(crate) fn internal_accepted_donation(&self, fundraiser_id: FundraiserId, fundraiser: &Fundraiser, paid_by: &AccountId, amount: u128) -> u128 {
+        let min_donation = self.internal_min_donation(fundraiser_id);
+        assert!(amount >= min_donation, "Abort. Donation is below the min donation of {} yoctoNEAR", min_donation);
+        assert!(self.internal_hard_cap_room(fundraiser_id, fundraiser) > 0, "Fundraiser has reached its hard cap");
+        let room = self.internal_donation_room(fundraiser_id, fundraiser, paid_by);
+        assert!(room > 0, "You have reached the max donation for this fundraiser");
+        cmp::min(amount, room)
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::accounts;
+    use near_sdk::testing_env;
+    use crate::test_helpers::{get_context, init_with_fundraiser, donate, ONE_NEAR, STORAGE_DEPOSIT};
+
+    fn setup(limits: DonationLimits) -> (Contract, FundraiserId) {
+        let (mut contract, fundraiser_id) = init_with_fundraiser(accounts(1));
+        testing_env!(get_context(accounts(1))
+            .storage_usage(env::storage_usage())
+            .attached_deposit(NearToken::from_yoctonear(STORAGE_DEPOSIT))
+            .build());
+        contract.set_donation_limits(fundraiser_id, limits);
+        (contract, fundraiser_id)
+    }
+
+    #[test]
+    fn min_donation_covers_storage_test() {
+        let (mut contract, fundraiser_id) = init_with_fundraiser(accounts(1));
+        //the longest account id possible takes the most storage
+        let donor_id: AccountId = "a".repeat(64).parse().unwrap();
+        testing_env!(get_context(donor_id)
+            .storage_usage(env::storage_usage())
+            .attached_deposit(NearToken::from_yoctonear(MIN_DONATION))
+            .build());
+        let initial_storage_usage = env::storage_usage();
+        contract.donate_to_fundraiser(fundraiser_id, None, None);
+        let storage_cost = env::storage_byte_cost().as_yoctonear() * u128::from(env::storage_usage() - initial_storage_usage);
+        assert!(storage_cost <= MIN_DONATION);
+    }
+
+    fn total_collected(contract: &Contract, fundraiser_id: FundraiserId) -> U128 {
+        contract.get_fundraiser_by_id(fundraiser_id).unwrap().fundraiser.total_collected
+    }
+
+    #[test]
+    fn hard_cap_test() {
+        let (mut contract, fundraiser_id) = setup(DonationLimits { hard_cap: Some(U128(5 * ONE_NEAR)), ..Default::default() });
+        donate(&mut contract, accounts(2), fundraiser_id, 3 * ONE_NEAR);
+        //only 2 NEAR are accepted, the rest is refunded
+        donate(&mut contract, accounts(3), fundraiser_id, 3 * ONE_NEAR);
+        assert_eq!(total_collected(&contract, fundraiser_id), U128(5 * ONE_NEAR));
+        assert_eq!(contract.internal_donor_total(fundraiser_id, &accounts(3)), 2 * ONE_NEAR);
+    }
+
+    #[test]
+    #[should_panic(expected = "Fundraiser has reached its hard cap")]
+    fn hard_cap_reached_test() {
+        let (mut contract, fundraiser_id) = setup(DonationLimits { hard_cap: Some(U128(ONE_NEAR)), ..Default::default() });
+        donate(&mut contract, accounts(2), fundraiser_id, ONE_NEAR);
+        donate(&mut contract, accounts(3), fundraiser_id, ONE_NEAR);
+    }
+
+    #[test]
+    fn max_per_donor_test() {
+        let (mut contract, fundraiser_id) = setup(DonationLimits { max_per_donor: Some(U128(ONE_NEAR)), ..Default::default() });
+        donate(&mut contract, accounts(2), fundraiser_id, 3 * ONE_NEAR);
+        donate(&mut contract, accounts(3), fundraiser_id, ONE_NEAR);
+        assert_eq!(total_collected(&contract, fundraiser_id), U128(2 * ONE_NEAR));
+    }
+
+    #[test]
+    #[should_panic(expected = "You have reached the max donation for this fundraiser")]
+    fn max_per_donor_on_behalf_test() {
+        let (mut contract, fundraiser_id) = setup(DonationLimits { max_per_donor: Some(U128(ONE_NEAR)), ..Default::default() });
+        //the limit follows the account that pays, not the ones it donates for
+        testing_env!(get_context(accounts(2)).attached_deposit(NearToken::from_yoctonear(ONE_NEAR)).build());
+        contract.donate_to_fundraiser(fundraiser_id, Some(accounts(3)), None);
+        testing_env!(get_context(accounts(2)).attached_deposit(NearToken::from_yoctonear(ONE_NEAR)).build());
+        contract.donate_to_fundraiser(fundraiser_id, Some(accounts(4)), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Abort. Donation is below the min donation of 50000000000000000000000 yoctoNEAR")]
+    fn zero_donation_test() {
+        let (mut contract, fundraiser_id) = init_with_fundraiser(accounts(1));
+        donate(&mut contract, accounts(2), fundraiser_id, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Abort. Donation is below the min donation")]
+    fn min_donation_test() {
+        let (mut contract, fundraiser_id) = setup(DonationLimits { min_donation: Some(U128(ONE_NEAR)), ..Default::default() });
+        donate(&mut contract, accounts(2), fundraiser_id, ONE_NEAR / 2);
+    }
+}
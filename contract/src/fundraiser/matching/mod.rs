@@ -0,0 +1,295 @@
+// Matching-fund pools: sponsors lock funds that are added on top of qualifying donations.
+
+use crate::*;
+
+pub type MatchingPoolId = u64;
+
+//ratios are expressed in basis points, 10_000 is a 1:1 match
+pub const MATCHING_RATIO_BASE: u32 = 10_000;
+//a single pool can match at most 10:1
+pub const MAX_MATCHING_RATIO: u32 = 100_000;
+
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+#[allow(non_camel_case_types)]
+pub enum MatchingTarget {
+    FUNDRAISER(FundraiserId),
+    CATEGORY(FundraiserCategory),
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct MatchingPool {
+    pub sponsor_id: AccountId,
+    pub target: MatchingTarget,
+    // matched amount per donated yoctoNEAR, in basis points
+    pub ratio: u32,
+    // max amount matched for a single donation
+    pub per_donation_cap: U128,
+    // max amount matched in total, locked when the pool is created
+    pub total_cap: U128,
+    // amount matched so far
+    pub matched: U128,
+    // When the pool stops matching, Unix epoch in milliseconds
+    pub expires_at: u64,
+}
+
+impl MatchingPool {
+    fn remaining(&self) -> u128 {
+        self.total_cap.0 - self.matched.0
+    }
+
+    fn is_open(&self, now: u64) -> bool {
+        now < self.expires_at && self.remaining() > 0
+    }
+
+    //the amount the pool adds on top of the given donation
+    fn match_for(&self, donation: u128) -> u128 {
+        let matched = mul_div(donation, u128::from(self.ratio), u128::from(MATCHING_RATIO_BASE));
+        std::cmp::min(std::cmp::min(matched, self.per_donation_cap.0), self.remaining())
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct JsonMatchingPool {
+    pub pool_id: MatchingPoolId,
+    pub pool: MatchingPool,
+}
+
+#[near_bindgen]
+impl Contract {
+    /*
+        creates a matching pool. The attached deposit must cover total_cap plus the storage of the pool,
+        anything above that is refunded.
+    */
+    #[payable]
+    pub fn create_matching_pool(
+        &mut self,
+        target: MatchingTarget,
+        ratio: u32,
+        per_donation_cap: U128,
+        total_cap: U128,
+        expires_at: u64,
+    ) -> MatchingPoolId {
+        if let MatchingTarget::FUNDRAISER(fundraiser_id) = &target {
+            assert!(self.fundraisers_by_id.get(fundraiser_id).is_some(), "Incorrect fundraiser id");
+        }
+        assert!(ratio > 0 && ratio <= MAX_MATCHING_RATIO, "Abort. Ratio must be between 1 and 100000 basis points");
+        assert!(per_donation_cap.0 > 0, "Abort. Per donation cap must be greater than 0");
+        assert!(total_cap.0 > 0, "Abort. Total cap must be greater than 0");
+        assert!(expires_at > env::block_timestamp_ms(), "Abort. Expiry date is in the past");
+
+        let initial_storage_usage = env::storage_usage();
+        let sponsor_id = env::predecessor_account_id();
+
+        self.matching_pool_counter += 1;
+        let pool_id: MatchingPoolId = self.matching_pool_counter;
+        let pool = MatchingPool {
+            sponsor_id,
+            target: target.clone(),
+            ratio,
+            per_donation_cap,
+            total_cap,
+            matched: U128(0),
+            expires_at,
+        };
+        self.matching_pools_by_id.insert(&pool_id, &pool);
+
+        let mut target_set = self.matching_pools_per_target.get(&target).unwrap_or_else(|| {
+            UnorderedSet::new(
+                StorageKey::MatchingPoolsPerTargetInner { target_hash: env::sha256_array(borsh::to_vec(&target).unwrap()) }
+            )
+        });
+        target_set.insert(&pool_id);
+        self.matching_pools_per_target.insert(&target, &target_set);
+
+        let storage_cost = env::storage_byte_cost().as_yoctonear() * u128::from(env::storage_usage() - initial_storage_usage);
+        let required = storage_cost + total_cap.0;
+        let deposit = env::attached_deposit().as_yoctonear();
+        assert!(deposit >= required, "Must attach {} yoctoNEAR to cover the pool and its storage", required);
+        if deposit - required > 1 {
+            Promise::new(env::predecessor_account_id()).transfer(NearToken::from_yoctonear(deposit - required)).detach();
+        }
+
+        pool_id
+    }
+
+    //once the pool expired anyone can close it, the unused funds and the storage go back to the sponsor
+    pub fn close_matching_pool(&mut self, pool_id: MatchingPoolId) -> U128 {
+        let pool = self.matching_pools_by_id.get(&pool_id).expect("Incorrect matching pool id");
+        let now = env::block_timestamp_ms();
+        assert!(
+            now >= pool.expires_at || env::predecessor_account_id() == pool.sponsor_id && pool.remaining() == 0,
+            "Matching pool has not expired yet"
+        );
+
+        let initial_storage_usage = env::storage_usage();
+        self.matching_pools_by_id.remove(&pool_id);
+        let mut target_set = self.matching_pools_per_target.get(&pool.target).unwrap();
+        target_set.remove(&pool_id);
+        if target_set.is_empty() {
+            self.matching_pools_per_target.remove(&pool.target);
+        } else {
+            self.matching_pools_per_target.insert(&pool.target, &target_set);
+        }
+        let released_storage = env::storage_byte_cost().as_yoctonear() * u128::from(initial_storage_usage.saturating_sub(env::storage_usage()));
+
+        let refund = pool.remaining() + released_storage;
+        if refund > 0 {
+            Promise::new(pool.sponsor_id).transfer(NearToken::from_yoctonear(refund)).detach();
+        }
+        U128(refund)
+    }
+
+    pub fn get_matching_pool(&self, pool_id: MatchingPoolId) -> Option<JsonMatchingPool> {
+        self.matching_pools_by_id.get(&pool_id).map(|pool| JsonMatchingPool { pool_id, pool })
+    }
+
+    //pools that currently match donations to the fundraiser, directly or through its category
+    pub fn get_matching_pools_for_fundraiser(&self, fundraiser_id: FundraiserId) -> Vec<JsonMatchingPool> {
+        if !self.can_view_fundraiser(fundraiser_id, None) {
+            return vec![];
+        }
+        let now = env::block_timestamp_ms();
+        self.internal_matching_pool_ids(fundraiser_id)
+            .into_iter()
+            .filter_map(|pool_id| self.get_matching_pool(pool_id))
+            .filter(|json_pool| json_pool.pool.is_open(now))
+            .collect()
+    }
+}
+
+impl Contract {
+    fn internal_matching_pool_ids(&self, fundraiser_id: FundraiserId) -> Vec<MatchingPoolId> {
+        let fundraiser = match self.fundraisers_by_id.get(&fundraiser_id) {
+            Some(fundraiser) => fundraiser,
+            None => return vec![],
+        };
+        [MatchingTarget::FUNDRAISER(fundraiser_id), MatchingTarget::CATEGORY(fundraiser.category)]
+            .iter()
+            .filter_map(|target| self.matching_pools_per_target.get(target))
+            .flat_map(|target_set| target_set.to_vec())
+            .collect()
+    }
+
+    //the owner, the team and the beneficiaries would get their own donations matched back to them
+    fn is_excluded_from_matching(&self, fundraiser_id: FundraiserId, fundraiser: &Fundraiser, account_id: &AccountId) -> bool {
+        account_id == fundraiser.owner_id()
+            || self.is_team_member(fundraiser_id, account_id)
+            || self.is_beneficiary(fundraiser_id, fundraiser, account_id)
+    }
+
+    /*
+        credits the fundraiser with the match of every open pool targeting it. Matched amounts are recorded
+        as donations of the sponsor, so they are refunded to the sponsor if the fundraiser is taken down.
+        Nothing is matched when the donor or the account that paid is an insider of the fundraiser.
+    */
+    pub(crate) fn internal_apply_matching(&mut self, fundraiser_id: FundraiserId, donor_id: &AccountId, paid_by: &AccountId, donation: u128) {
+        let fundraiser: Fundraiser = self.fundraisers_by_id.get(&fundraiser_id).unwrap();
+        if self.is_excluded_from_matching(fundraiser_id, &fundraiser, donor_id) || self.is_excluded_from_matching(fundraiser_id, &fundraiser, paid_by) {
+            return;
+        }
+        let now = env::block_timestamp_ms();
+        for pool_id in self.internal_matching_pool_ids(fundraiser_id) {
+            let mut pool = self.matching_pools_by_id.get(&pool_id).unwrap();
+            if !pool.is_open(now) || &pool.sponsor_id == donor_id || &pool.sponsor_id == paid_by {
+                continue;
+            }
+            //matched donations count towards the hard cap too
+            let fundraiser: Fundraiser = self.fundraisers_by_id.get(&fundraiser_id).unwrap();
+            let matched = std::cmp::min(pool.match_for(donation), self.internal_hard_cap_room(fundraiser_id, &fundraiser));
+            if matched == 0 {
+                continue;
+            }
+            pool.matched = U128(pool.matched.0 + matched);
+            self.matching_pools_by_id.insert(&pool_id, &pool);
+            self.internal_record_donation(fundraiser_id, &pool.sponsor_id, &pool.sponsor_id, matched);
+        }
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::accounts;
+    use near_sdk::testing_env;
+    use crate::test_helpers::{get_context, init_with_fundraiser, donate, ONE_NEAR};
+
+    fn create_pool(contract: &mut Contract, target: MatchingTarget, ratio: u32, per_donation_cap: u128, total_cap: u128) -> MatchingPoolId {
+        testing_env!(get_context(accounts(4))
+            .storage_usage(env::storage_usage())
+            .attached_deposit(NearToken::from_yoctonear(total_cap + ONE_NEAR / 10))
+            .build());
+        contract.create_matching_pool(target, ratio, U128(per_donation_cap), U128(total_cap), 1_000)
+    }
+
+    fn total_collected(contract: &Contract, fundraiser_id: FundraiserId) -> u128 {
+        contract.fundraisers_by_id.get(&fundraiser_id).unwrap().total_collected.0
+    }
+
+    #[test]
+    fn matching_pool_caps_test() {
+        let (mut contract, fundraiser_id) = init_with_fundraiser(accounts(0));
+        let pool_id = create_pool(&mut contract, MatchingTarget::FUNDRAISER(fundraiser_id), MATCHING_RATIO_BASE, 3 * ONE_NEAR, 5 * ONE_NEAR);
+
+        donate(&mut contract, accounts(2), fundraiser_id, 2 * ONE_NEAR);
+        assert_eq!(total_collected(&contract, fundraiser_id), 4 * ONE_NEAR);
+        //capped per donation
+        donate(&mut contract, accounts(2), fundraiser_id, 4 * ONE_NEAR);
+        assert_eq!(total_collected(&contract, fundraiser_id), 11 * ONE_NEAR);
+        //capped by what is left in the pool
+        donate(&mut contract, accounts(2), fundraiser_id, 2 * ONE_NEAR);
+        assert_eq!(total_collected(&contract, fundraiser_id), 13 * ONE_NEAR);
+
+        assert_eq!(contract.get_matching_pool(pool_id).unwrap().pool.matched, U128(5 * ONE_NEAR));
+        assert!(contract.get_matching_pools_for_fundraiser(fundraiser_id).is_empty());
+    }
+
+    #[test]
+    fn insider_donations_not_matched_test() {
+        let (mut contract, fundraiser_id) = init_with_fundraiser(accounts(0));
+        create_pool(&mut contract, MatchingTarget::FUNDRAISER(fundraiser_id), MATCHING_RATIO_BASE, ONE_NEAR, 2 * ONE_NEAR);
+
+        testing_env!(get_context(accounts(0)).attached_deposit(NearToken::from_yoctonear(ONE_NEAR)).build());
+        contract.donate_to_fundraiser(fundraiser_id, None, None);
+        //paying for someone else doesn't help either
+        testing_env!(get_context(accounts(0)).attached_deposit(NearToken::from_yoctonear(ONE_NEAR)).build());
+        contract.donate_to_fundraiser(fundraiser_id, Some(accounts(3)), None);
+        assert_eq!(total_collected(&contract, fundraiser_id), 2 * ONE_NEAR);
+    }
+
+    #[test]
+    fn category_matching_pool_test() {
+        let (mut contract, fundraiser_id) = init_with_fundraiser(accounts(0));
+        create_pool(&mut contract, MatchingTarget::CATEGORY(FundraiserCategory::OTHER), MATCHING_RATIO_BASE / 2, ONE_NEAR, ONE_NEAR);
+        create_pool(&mut contract, MatchingTarget::CATEGORY(FundraiserCategory::MEDICAL), MATCHING_RATIO_BASE, ONE_NEAR, ONE_NEAR);
+
+        donate(&mut contract, accounts(2), fundraiser_id, ONE_NEAR);
+        assert_eq!(total_collected(&contract, fundraiser_id), ONE_NEAR + ONE_NEAR / 2);
+        assert_eq!(contract.get_matching_pools_for_fundraiser(fundraiser_id).len(), 1);
+    }
+
+    #[test]
+    fn close_matching_pool_test() {
+        let (mut contract, fundraiser_id) = init_with_fundraiser(accounts(0));
+        let pool_id = create_pool(&mut contract, MatchingTarget::FUNDRAISER(fundraiser_id), MATCHING_RATIO_BASE, ONE_NEAR, 2 * ONE_NEAR);
+        donate(&mut contract, accounts(2), fundraiser_id, ONE_NEAR);
+
+        testing_env!(get_context(accounts(3)).block_timestamp(1_000 * 1_000_000).build());
+        let refund = contract.close_matching_pool(pool_id);
+        assert!(refund.0 > ONE_NEAR);
+        assert!(contract.get_matching_pool(pool_id).is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "Matching pool has not expired yet")]
+    fn close_matching_pool_before_expiry_test() {
+        let (mut contract, fundraiser_id) = init_with_fundraiser(accounts(0));
+        let pool_id = create_pool(&mut contract, MatchingTarget::FUNDRAISER(fundraiser_id), MATCHING_RATIO_BASE, ONE_NEAR, 2 * ONE_NEAR);
+        testing_env!(get_context(accounts(4)).build());
+        contract.close_matching_pool(pool_id);
+    }
+}
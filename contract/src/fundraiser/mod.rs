@@ -1,9 +1,46 @@
 use crate::*;
 
+pub use moderation::*;
+pub use category::*;
+pub use sort::*;
+pub use updates::*;
+pub use comments::*;
+pub use pledges::*;
+pub use matching::*;
+pub use rounds::*;
+pub use rewards::*;
+pub use payouts::*;
+pub use team::*;
+pub use vesting::*;
+pub use limits::*;
+pub use visibility::*;
+pub use cooling_off::*;
+
+mod moderation;
+mod category;
+mod sort;
+mod updates;
+mod comments;
+mod pledges;
+mod matching;
+mod rounds;
+mod rewards;
+mod archive;
+mod vouchers;
+mod tokens;
+mod payouts;
+mod team;
+mod vesting;
+mod limits;
+mod visibility;
+mod cooling_off;
 
 pub type FundraiserId = u32;
 
+const MAX_DEDICATION_LENGTH: usize = 280;
+
 #[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[borsh(crate = "near_sdk::borsh")]
 #[serde(crate = "near_sdk::serde")]
 pub enum FundraiserStatus {
     ACTIVE,
@@ -12,12 +49,25 @@ pub enum FundraiserStatus {
 }
 
 #[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[borsh(crate = "near_sdk::borsh")]
 #[serde(crate = "near_sdk::serde")]
 pub struct Fundraiser {
     owner_id: AccountId,
     title: String,
     description: String,
     status: FundraiserStatus,
+    moderation_status: ModerationStatus,
+    visibility: FundraiserVisibility,
+    category: FundraiserCategory,
+    tags: Vec<String>,
+    // amount the fundraiser is trying to collect, if any
+    goal: Option<U128>,
+    // total of all the donations received so far
+    total_collected: U128,
+    // Unix epoch in milliseconds
+    created_at: u64,
+    // When the fundraiser ends, Unix epoch in milliseconds
+    ends_at: Option<u64>,
 }
 
 #[derive(Serialize, Deserialize, PartialEq, Debug)]
@@ -25,53 +75,98 @@ pub struct Fundraiser {
 pub struct JsonFundraiser {
     pub fundraiser: Fundraiser,
     pub fundraiser_id: FundraiserId,
-    pub token_id: TokenId,
-    pub token: Token,
-    pub token_metadata: TokenMetadata,
+    //token minted when the fundraiser was created, None if it was burned
+    pub campaign_token: Option<JsonToken>,
+}
+
+//arguments of add_new_fundraiser
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct NewFundraiser {
+    pub title: String,
+    pub description: String,
+    pub status: FundraiserStatus,
+    pub category: FundraiserCategory,
+    pub tags: Vec<String>,
+    // amount the fundraiser is trying to collect, if any
+    pub goal: Option<U128>,
+    // When the fundraiser ends, Unix epoch in milliseconds
+    pub ends_at: Option<u64>,
 }
 
 
 #[near_bindgen]
 impl Contract {
-    pub fn get_all_fundraisers(&self, page: Option<u32>) -> Vec<JsonFundraiser> {
-        let (from_index, take) = pagination(page);
-        self.fundraisers_by_id.keys()
-            .skip(from_index as usize)
-            .take(take as usize)
-            .map(|fundraiser_id: FundraiserId| self.get_fundraiser_by_id(fundraiser_id.clone()).unwrap())
-            .collect()
+    /*
+        lists the visible fundraisers matching the optional filters.
+        Without a sort the fundraisers come from the narrowest category/tag/status index and the cursor
+        is a position in it, with a sort they come from the matching sorted index and the cursor is the
+        key of the last returned fundraiser. Either way pass `next_cursor` back to get the next page.
+    */
+    pub fn get_all_fundraisers(
+        &self,
+        category: Option<FundraiserCategory>,
+        tag: Option<String>,
+        status: Option<FundraiserStatus>,
+        sort: Option<FundraiserSort>,
+        cursor: Option<String>,
+        limit: Option<u64>,
+    ) -> Page<JsonFundraiser> {
+        let tag = tag.map(|tag| normalize_tag(&tag));
+        let page: Page<FundraiserId> = if let Some(sort) = sort {
+            let now = env::block_timestamp_ms();
+            self.internal_sorted_fundraiser_ids(&sort, cursor, limit, |_, fundraiser| {
+                fundraiser.matches_listing_filters(&category, &tag, &status)
+                    && (sort != FundraiserSort::ENDING_SOONEST || fundraiser.ends_at.is_some_and(|ends_at| ends_at > now))
+            })
+        } else {
+            self.internal_filtered_fundraiser_ids(&category, &tag, &status, cursor, limit)
+        };
+        Page {
+            items: page.items.into_iter()
+                .filter_map(|fundraiser_id| self.get_fundraiser_by_id(fundraiser_id))
+                .collect(),
+            next_cursor: page.next_cursor,
+        }
     }
 
+    //allowlist-only fundraisers are not returned, see get_fundraiser_for_account
     pub fn get_fundraiser_by_id(&self, id: FundraiserId) -> Option<JsonFundraiser> {
-        if let Some(fundraiser) = self.fundraisers_by_id.get(&id) {
-            let token_id: TokenId = id.to_string();
-            if let Some(jsonToken) = self.nft_token(token_id.clone()) {
-                Some(JsonFundraiser {
-                    fundraiser,
-                    fundraiser_id: id,
-                    token: self.tokens_by_id.get(&token_id).unwrap(),
-                    token_id,
-                    token_metadata: jsonToken.metadata,
-                })
-            } else {
-                None
-            }
+        self.internal_visible_fundraiser(id, None)
+    }
+
+    //get the number of fundraisers created by a given owner
+    pub fn get_fundraiser_count_by_owner(&self, owner_id: AccountId) -> U128 {
+        if let Some(fundraisers_for_owner_set) = self.fundraiser_per_owner.get(&owner_id) {
+            U128(fundraisers_for_owner_set.len() as u128)
         } else {
-            None
+            U128(0)
         }
     }
 
+    //Query for all the fundraisers of an owner, including hidden ones. Allowlist-only ones are left out, see get_own_fundraisers
+    pub fn get_fundraisers_by_owner(
+        &self,
+        owner_id: AccountId,
+        from_index: Option<U128>,
+        limit: Option<u64>,
+    ) -> Vec<JsonFundraiser> {
+        self.internal_fundraisers_by_owner(&owner_id, from_index, limit, None)
+    }
+
     #[payable]
-    pub fn add_new_fundraiser(
-        &mut self,
-        title: String,
-        description: String,
-        status: FundraiserStatus,
-        token_metadata: TokenMetadata,
-    ) {
+    pub fn add_new_fundraiser(&mut self, fundraiser: NewFundraiser, token_metadata: TokenMetadata) {
+        let NewFundraiser { title, description, status, category, tags, goal, ends_at } = fundraiser;
         assert!(title != "", "Abort. Title is empty");
         assert!(title.len() <= 1000, "Abort. Title is longer then 1000 characters");
         assert!(description.len() <= 2000, "Abort. Description is longer then 2000 characters");
+        if let Some(goal) = goal {
+            assert!(goal.0 > 0, "Abort. Goal must be greater than 0");
+        }
+        let created_at = env::block_timestamp_ms();
+        if let Some(ends_at) = ends_at {
+            assert!(ends_at > created_at, "Abort. End date is in the past");
+        }
 
         let owner_id = env::predecessor_account_id();
         let fundraiser = Fundraiser {
@@ -79,6 +174,14 @@ impl Contract {
             title,
             description,
             status,
+            moderation_status: ModerationStatus::VISIBLE,
+            visibility: FundraiserVisibility::PUBLIC,
+            category,
+            tags: normalize_tags(tags),
+            goal,
+            total_collected: U128(0),
+            created_at,
+            ends_at,
         };
 
         let mut f_owner_set = self.fundraiser_per_owner.get(&owner_id).unwrap_or_else(|| {
@@ -97,48 +200,49 @@ impl Contract {
         self.fundraiser_counter += 1;
 
         let fundraiser_id: FundraiserId = self.fundraiser_counter.clone();
-        let token_id: TokenId = self.fundraiser_counter.clone().to_string();
 
         f_owner_set.insert(&fundraiser_id);
         self.fundraiser_per_owner.insert(&owner_id, &f_owner_set);
 
-        self.nft_mint(token_id, owner_id, token_metadata);
+        //the owner pays for the storage of the campaign token
+        let initial_storage_usage = env::storage_usage();
+        let token_id = self.internal_next_token_id("campaign");
+        self.internal_mint(token_id.clone(), owner_id, token_metadata);
+        refund_deposit(env::storage_usage() - initial_storage_usage);
+        self.campaign_token_by_fundraiser.insert(&fundraiser_id, &token_id);
+        self.internal_add_token_to_fundraiser(fundraiser_id, &token_id);
 
-        self.fundraisers_by_id.insert(&fundraiser_id.clone(), &fundraiser);
+        self.internal_index_fundraiser(fundraiser_id, &fundraiser);
+        self.internal_update_fundraiser(fundraiser_id, &fundraiser);
     }
 
+    /*
+        donates the attached deposit, the part above the donation limits of the fundraiser is refunded to the caller.
+        With `on_behalf_of` the donation and the rewards it unlocks are credited to that account instead of the caller,
+        e.g. a company donating for its employees. The limits, the allowlist, quadratic funding and refunds still
+        go by the caller. The dedication is a short public message, "in memory of ..."
+    */
     #[payable]
-    pub fn donate_to_fundraiser(&mut self, fundraiser_id: FundraiserId)
+    pub fn donate_to_fundraiser(&mut self, fundraiser_id: FundraiserId, on_behalf_of: Option<AccountId>, dedication: Option<String>)
     {
+        let deposit = env::attached_deposit().as_yoctonear();
+        let payer_id = env::predecessor_account_id();
+        let donor_id = on_behalf_of.unwrap_or_else(|| payer_id.clone());
+        if let Some(dedication) = &dedication {
+            assert!(dedication.len() <= MAX_DEDICATION_LENGTH, "Abort. Dedication is longer then 280 characters");
+        }
+        //donations whose cooling-off period is over count before this one
+        self.internal_settle_donations(fundraiser_id, MAX_SETTLEMENTS_PER_CALL);
         let fundraiser: Fundraiser = self.fundraisers_by_id.get(&fundraiser_id).expect("Incorrect fundraiser id");
-        let donation = env::attached_deposit();
-        let donor_id = env::predecessor_account_id();
-
-        let mut fundraiser_donations_list = self.fundraisers_donations.get(&fundraiser_id).unwrap_or_else(|| {
-            // if there is no donations yet -> initialize lookup for the donor
-            let prefix: Vec<u8> = [
-                b"f_donations".as_slice(),
-                &near_sdk::env::sha256_array(donor_id.as_bytes()),
-            ]
-                .concat();
-            UnorderedMap::new(prefix)
-        });
-        let mut donations_of_donor = fundraiser_donations_list.get(&donor_id).unwrap_or_else(|| {
-            // if there is no donations for donor -> init it
-            let prefix: Vec<u8> = [
-                b"donor".as_slice(),
-                &near_sdk::env::sha256_array(donor_id.as_bytes()),
-            ].concat();
-            Vector::new(prefix)
-        });
-        donations_of_donor.push(&u128::from(donation));
-        fundraiser_donations_list.insert(&donor_id, &donations_of_donor);
-        self.fundraisers_donations.insert(&fundraiser_id, &fundraiser_donations_list);
-
-        let token_id: TokenId = fundraiser_id.to_string();
-        self.nft_transfer(donor_id.clone(), token_id, None);
-        log!("{:?}",donation);
-        log!("{:?}", self.fundraisers_donations.get(&fundraiser_id).unwrap().get(&donor_id).unwrap().to_vec())
+        let donation = self.internal_accepted_donation(fundraiser_id, &fundraiser, &payer_id, deposit);
+        if self.get_cooling_off_period(fundraiser_id) > 0 {
+            self.internal_queue_donation(fundraiser_id, &fundraiser, &donor_id, &payer_id, donation, dedication);
+        } else {
+            self.internal_donate(fundraiser_id, &donor_id, &payer_id, donation, env::block_timestamp_ms(), dedication);
+        }
+        if deposit > donation {
+            Promise::new(payer_id).transfer(NearToken::from_yoctonear(deposit - donation)).detach();
+        }
     }
 
     // pub fn update_zoo(
@@ -174,16 +278,137 @@ impl Contract {
     // }
 }
 
+impl Fundraiser {
+    pub(crate) fn owner_id(&self) -> &AccountId {
+        &self.owner_id
+    }
+
+    //not hidden or taken down by a moderator
+    pub(crate) fn is_visible(&self) -> bool {
+        self.moderation_status == ModerationStatus::VISIBLE
+    }
+
+    pub(crate) fn is_accepting_donations(&self) -> bool {
+        self.is_visible() && self.status != FundraiserStatus::DRAFT && !self.is_over()
+    }
+
+    //completed by its owner or past its end date
+    pub(crate) fn is_over(&self) -> bool {
+        self.status == FundraiserStatus::COMPLETED || self.ends_at.is_some_and(|ends_at| env::block_timestamp_ms() >= ends_at)
+    }
+}
+
+impl Contract {
+    pub(crate) fn internal_fundraisers_by_owner(
+        &self,
+        owner_id: &AccountId,
+        from_index: Option<U128>,
+        limit: Option<u64>,
+        viewer: Option<&AccountId>,
+    ) -> Vec<JsonFundraiser> {
+        let fundraisers = if let Some(fundraisers_for_owner_set) = self.fundraiser_per_owner.get(owner_id) {
+            fundraisers_for_owner_set
+        } else {
+            return vec![];
+        };
+        fundraisers.iter()
+            .skip(page_start(from_index))
+            .take(page_limit(limit))
+            .filter_map(|fundraiser_id| self.internal_visible_fundraiser(fundraiser_id, viewer))
+            .collect()
+    }
+
+    //details of the fundraiser whatever its visibility, views go through internal_visible_fundraiser
+    pub(crate) fn internal_json_fundraiser(&self, id: FundraiserId) -> Option<JsonFundraiser> {
+        let fundraiser = self.fundraisers_by_id.get(&id)?;
+        Some(JsonFundraiser {
+            fundraiser,
+            fundraiser_id: id,
+            campaign_token: self.campaign_token_by_fundraiser.get(&id).and_then(|token_id| self.nft_token(token_id)),
+        })
+    }
+
+    //donation of `donation` yoctoNEAR from `donor_id`, the funds must already be held by the contract
+    //`donor_id` is credited for the donation, `paid_by` is the account the funds came from
+    pub(crate) fn internal_donate(
+        &mut self,
+        fundraiser_id: FundraiserId,
+        donor_id: &AccountId,
+        paid_by: &AccountId,
+        donation: u128,
+        donated_at: u64,
+        dedication: Option<String>,
+    ) {
+        let fundraiser: Fundraiser = self.fundraisers_by_id.get(&fundraiser_id).expect("Incorrect fundraiser id");
+        assert!(fundraiser.is_accepting_donations(), "Fundraiser is not accepting donations");
+        assert!(
+            self.is_allowed_on_fundraiser(fundraiser_id, &fundraiser, donor_id) && self.is_allowed_on_fundraiser(fundraiser_id, &fundraiser, paid_by),
+            "Only allowlisted accounts can donate to this fundraiser"
+        );
+
+        let previous_total = self.internal_donor_total(fundraiser_id, donor_id);
+        self.internal_record_donation(fundraiser_id, donor_id, paid_by, donation);
+        self.internal_mint_rewards(fundraiser_id, donor_id, previous_total, previous_total + donation);
+        self.internal_award_donation_badges(fundraiser_id, donor_id, previous_total, previous_total + donation);
+        self.internal_apply_matching(fundraiser_id, donor_id, paid_by, donation);
+        //owners backing their own fundraiser don't count towards quadratic funding, even through someone else.
+        //contributions go by the account that paid, so donating on behalf of many accounts doesn't add donors
+        if donor_id != &fundraiser.owner_id && paid_by != &fundraiser.owner_id {
+            self.internal_record_round_contribution(fundraiser_id, paid_by, donation, donated_at);
+        }
+
+        if paid_by != donor_id || dedication.is_some() {
+            EventLog::new(EventLogVariant::DonationGift(vec![DonationGiftLog {
+                fundraiser_id,
+                donor_id: donor_id.clone(),
+                paid_by: paid_by.clone(),
+                amount: U128(donation),
+                dedication,
+            }])).emit();
+        }
+    }
+
+    //adds the donation to fundraisers_donations, to what `paid_by` paid and to the total collected by the fundraiser
+    pub(crate) fn internal_record_donation(&mut self, fundraiser_id: FundraiserId, donor_id: &AccountId, paid_by: &AccountId, donation: u128) {
+        let mut fundraiser: Fundraiser = self.fundraisers_by_id.get(&fundraiser_id).expect("Incorrect fundraiser id");
+
+        let mut fundraiser_donations_list = self.fundraisers_donations.get(&fundraiser_id).unwrap_or_else(|| {
+            // if there is no donations yet -> initialize lookup for the fundraiser
+            let prefix: Vec<u8> = [
+                b"f_donations".as_slice(),
+                &near_sdk::env::sha256_array(fundraiser_id.to_string().as_bytes()),
+            ]
+                .concat();
+            UnorderedMap::new(prefix)
+        });
+        let mut donations_of_donor = fundraiser_donations_list.get(donor_id).unwrap_or_else(|| {
+            // if there is no donations for donor -> init it
+            let prefix: Vec<u8> = [
+                b"donor".as_slice(),
+                &near_sdk::env::sha256_array(format!("{}:{}", fundraiser_id, donor_id).as_bytes()),
+            ].concat();
+            Vector::new(prefix)
+        });
+        donations_of_donor.push(&donation);
+        fundraiser_donations_list.insert(donor_id, &donations_of_donor);
+        self.fundraisers_donations.insert(&fundraiser_id, &fundraiser_donations_list);
+        let paid = self.internal_paid_total(fundraiser_id, paid_by);
+        self.donations_paid_by.insert(&(fundraiser_id, paid_by.clone()), &(paid + donation));
+
+        fundraiser.total_collected = U128(fundraiser.total_collected.0 + donation);
+        self.internal_update_fundraiser(fundraiser_id, &fundraiser);
+    }
+}
+
 #[cfg(all(test, not(target_arch = "wasm32")))]
 mod tests {
     use super::*;
-    use std::iter::repeat;
     use test::test_helpers::init;
     use near_sdk::test_utils::{accounts, VMContextBuilder};
-    use near_sdk::{Balance, testing_env};
-    use crate::test_helpers::get_context;
+    use near_sdk::testing_env;
+    use crate::test_helpers::{get_context, empty_token_metadata, new_fundraiser};
 
-    const MINT_STORAGE_COST: u128 = 5870000000000000000000;
+    const MINT_STORAGE_COST: u128 = 6000000000000000000000;
 
     fn attach_dep_for_adding_fundraiser() -> Contract {
         let mut context = get_context(accounts(0));
@@ -192,7 +417,7 @@ mod tests {
 
         testing_env!(context
             .storage_usage(env::storage_usage())
-            .attached_deposit(MINT_STORAGE_COST)
+            .attached_deposit(NearToken::from_yoctonear(MINT_STORAGE_COST))
             .predecessor_account_id(accounts(0))
             .build());
 
@@ -221,7 +446,6 @@ mod tests {
     //     let fundraiser: Fundraiser = contract.fundraisers_by_id.get(&fundraiser_id).expect("incorrect id");
     //     let donation:Balance = 1_000_000_000;
     //     let donor_id = accounts(2);
-    //     log!("{}", donation);
     //     let mut fundraiser_donations_list = contract.fundraisers_donations.get(&fundraiser_id).unwrap_or_else(|| {
     //         // if there is no donations yet -> initialize lookup for the donor
     //         let prefix: Vec<u8> = [
@@ -244,8 +468,6 @@ mod tests {
     //     donations_of_donor.push(&u128::from(2000 as Balance)); // todo make to store a lot of equal values
     //     fundraiser_donations_list.insert(&donor_id, &donations_of_donor);
     //     contract.fundraisers_donations.insert(&fundraiser_id, &fundraiser_donations_list);
-    //     log!("{:?}",donation);
-    //     log!("{:?}", contract.fundraisers_donations.get(&fundraiser_id).unwrap().get(&donor_id).unwrap().to_vec());
     //     panic!()
     // }
 
@@ -265,6 +487,14 @@ mod tests {
             title: "".to_string(),
             description: "".to_string(),
             status: FundraiserStatus::ACTIVE,
+            moderation_status: ModerationStatus::VISIBLE,
+            visibility: FundraiserVisibility::PUBLIC,
+            category: FundraiserCategory::OTHER,
+            tags: vec![],
+            goal: None,
+            total_collected: U128(0),
+            created_at: 0,
+            ends_at: None,
         };
         let mut fundraiser_set = contract.fundraiser_per_owner.get(&owner_id).unwrap_or_else(|| {
             UnorderedSet::new(vec![])
@@ -295,12 +525,12 @@ mod tests {
 
         contract.token_metadata_by_id.insert(&token_id, &token_metadata);
 
+        contract.campaign_token_by_fundraiser.insert(&fundraiser_id, &token_id);
+
         let json_fundraiser: JsonFundraiser = JsonFundraiser {
             fundraiser,
             fundraiser_id,
-            token_id,
-            token,
-            token_metadata,
+            campaign_token: Some(JsonToken { token_id, owner_id: token.owner_id, metadata: token_metadata }),
         };
         assert_eq!(contract.get_fundraiser_by_id(1), Some(json_fundraiser));
     }
@@ -310,14 +540,14 @@ mod tests {
     fn get_all_fundraisers_empty_test() {
         let contract = init(accounts(1));
         let empty: Vec<JsonFundraiser> = Vec::new();
-        assert_eq!(contract.get_all_fundraisers(Some(1)), empty);
+        assert_eq!(contract.get_all_fundraisers(None, None, None, None, None, None).items, empty);
     }
 
     #[test]
     fn get_all_fundraisers_test() {
         let mut contract = attach_dep_for_adding_fundraiser();
 
-        contract.add_new_fundraiser("test".to_string(), "".to_string(), FundraiserStatus::ACTIVE, TokenMetadata {
+        contract.add_new_fundraiser(new_fundraiser("test"), TokenMetadata {
             title: None,
             description: None,
             media: None,
@@ -331,8 +561,48 @@ mod tests {
             reference: None,
             reference_hash: None,
         });
-        assert_eq!(contract.get_all_fundraisers(Some(1)).len(), 1);
-        assert_eq!(contract.get_all_fundraisers(None).len(), 1);
+        assert_eq!(contract.get_all_fundraisers(None, None, None, None, None, None).items.len(), 1);
+        assert_eq!(contract.get_all_fundraisers(None, None, None, Some(FundraiserSort::NEWEST), None, None).items.len(), 1);
+    }
+
+    #[test]
+    fn get_fundraisers_by_owner_test() {
+        let mut contract = attach_dep_for_adding_fundraiser();
+        contract.add_new_fundraiser(new_fundraiser("first"), empty_token_metadata());
+        testing_env!(get_context(accounts(0))
+            .storage_usage(env::storage_usage())
+            .attached_deposit(NearToken::from_yoctonear(MINT_STORAGE_COST))
+            .build());
+        contract.add_new_fundraiser(NewFundraiser { status: FundraiserStatus::DRAFT, ..new_fundraiser("second") }, empty_token_metadata());
+
+        assert_eq!(contract.get_fundraiser_count_by_owner(accounts(0)), U128(2));
+        assert_eq!(contract.get_fundraiser_count_by_owner(accounts(1)), U128(0));
+        assert_eq!(contract.get_fundraisers_by_owner(accounts(0), None, None).len(), 2);
+        let page = contract.get_fundraisers_by_owner(accounts(0), Some(U128(1)), Some(1));
+        assert_eq!(page.len(), 1);
+        assert_eq!(page[0].fundraiser.title, "second".to_string());
+        assert!(contract.get_fundraisers_by_owner(accounts(1), None, None).is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "Fundraiser is not accepting donations")]
+    fn donate_after_end_test() {
+        let mut contract = attach_dep_for_adding_fundraiser();
+        contract.add_new_fundraiser(NewFundraiser { ends_at: Some(1_000), ..new_fundraiser("test") }, empty_token_metadata());
+        testing_env!(get_context(accounts(2))
+            .block_timestamp(1_000 * 1_000_000)
+            .attached_deposit(NearToken::from_yoctonear(MIN_DONATION))
+            .build());
+        contract.donate_to_fundraiser(1, None, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Fundraiser is not accepting donations")]
+    fn donate_to_draft_test() {
+        let mut contract = attach_dep_for_adding_fundraiser();
+        contract.add_new_fundraiser(NewFundraiser { status: FundraiserStatus::DRAFT, ..new_fundraiser("test") }, empty_token_metadata());
+        testing_env!(get_context(accounts(2)).attached_deposit(NearToken::from_yoctonear(MIN_DONATION)).build());
+        contract.donate_to_fundraiser(1, None, None);
     }
 
     // #[should_panic(expected = "Abort. Address is longer then 1000 characters")]
@@ -341,7 +611,7 @@ mod tests {
     #[should_panic] // we are not attaching any deposit so it would panic
     fn add_new_fundraiser_panic_test() {
         let mut contract = init(accounts(1));
-        contract.add_new_fundraiser("test".to_string(), "".to_string(), FundraiserStatus::ACTIVE, TokenMetadata {
+        contract.add_new_fundraiser(new_fundraiser("test"), TokenMetadata {
             title: None,
             description: None,
             media: None,
@@ -361,7 +631,7 @@ mod tests {
     #[should_panic]
     fn add_new_fundraiser_validation_title_test() {
         let mut contract = attach_dep_for_adding_fundraiser();
-        contract.add_new_fundraiser("".to_string(), "".to_string(), FundraiserStatus::ACTIVE, TokenMetadata {
+        contract.add_new_fundraiser(new_fundraiser(""), TokenMetadata {
             title: None,
             description: None,
             media: None,
@@ -383,7 +653,7 @@ mod tests {
     fn add_new_fundraiser_validation_description_test() {
         let mut contract = attach_dep_for_adding_fundraiser();
 
-        contract.add_new_fundraiser("test".to_string(), repeat("X").take(2001).collect::<String>(), FundraiserStatus::ACTIVE, TokenMetadata {
+        contract.add_new_fundraiser(NewFundraiser { description: "X".repeat(2001), ..new_fundraiser("test") }, TokenMetadata {
             title: None,
             description: None,
             media: None,
@@ -0,0 +1,287 @@
+// Reporting of fundraisers and the moderator tools to hide or take them down.
+
+use crate::*;
+
+const MAX_REPORT_COMMENT_LENGTH: usize = 500;
+
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+#[allow(non_camel_case_types)]
+pub enum ModerationStatus {
+    VISIBLE,
+    // excluded from listings and closed for donations
+    HIDDEN,
+    // same as hidden, plus donors can claim their donations back. Cannot be undone
+    TAKEN_DOWN,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub enum ReportReason {
+    SCAM,
+    SPAM,
+    INAPPROPRIATE,
+    DUPLICATE,
+    OTHER,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct Report {
+    pub reporter_id: AccountId,
+    pub reason: ReportReason,
+    pub comment: Option<String>,
+    // Unix epoch in milliseconds
+    pub created_at: u64,
+}
+
+#[near_bindgen]
+impl Contract {
+    //any account can report a fundraiser once, the reporter pays for the storage
+    #[payable]
+    pub fn report_fundraiser(&mut self, fundraiser_id: FundraiserId, reason: ReportReason, comment: Option<String>) {
+        let fundraiser: Fundraiser = self.fundraisers_by_id.get(&fundraiser_id).expect("Incorrect fundraiser id");
+        assert!(
+            self.is_allowed_on_fundraiser(fundraiser_id, &fundraiser, &env::predecessor_account_id()),
+            "Only allowlisted accounts can report this fundraiser"
+        );
+        if let Some(comment) = &comment {
+            assert!(comment.len() <= MAX_REPORT_COMMENT_LENGTH, "Abort. Comment is longer then 500 characters");
+        }
+        let initial_storage_usage = env::storage_usage();
+        let reporter_id = env::predecessor_account_id();
+
+        let mut reports = self.fundraiser_reports.get(&fundraiser_id).unwrap_or_else(|| {
+            Vector::new(StorageKey::FundraiserReportsInner { fundraiser_id })
+        });
+        assert!(
+            !reports.iter().any(|report| report.reporter_id == reporter_id),
+            "You have already reported this fundraiser"
+        );
+        reports.push(&Report {
+            reporter_id,
+            reason,
+            comment,
+            created_at: env::block_timestamp_ms(),
+        });
+        self.fundraiser_reports.insert(&fundraiser_id, &reports);
+        self.reported_fundraisers.insert(&fundraiser_id);
+
+        refund_deposit(env::storage_usage() - initial_storage_usage);
+    }
+
+    pub fn get_fundraiser_reports(&self, fundraiser_id: FundraiserId, cursor: Option<String>, limit: Option<u64>) -> Page<Report> {
+        if let Some(reports) = self.fundraiser_reports.get(&fundraiser_id).filter(|_| self.can_view_fundraiser(fundraiser_id, None)) {
+            paginate(reports.iter(), cursor, limit)
+        } else {
+            paginate(std::iter::empty(), cursor, limit)
+        }
+    }
+
+    /*
+        the review queue: fundraisers with reports that no moderator has acted upon yet.
+        Allowlist-only fundraisers are left out, moderators see them with get_fundraiser_for_account.
+    */
+    pub fn get_reported_fundraisers(&self, cursor: Option<String>, limit: Option<u64>) -> Page<JsonFundraiser> {
+        paginate(
+            self.reported_fundraisers.iter().filter_map(|fundraiser_id| self.internal_visible_fundraiser(fundraiser_id, None)),
+            cursor,
+            limit,
+        )
+    }
+
+    pub fn get_moderators(&self) -> Vec<AccountId> {
+        self.moderators.to_vec()
+    }
+
+    pub fn add_moderator(&mut self, account_id: AccountId) {
+        self.assert_contract_owner();
+        self.moderators.insert(&account_id);
+    }
+
+    pub fn remove_moderator(&mut self, account_id: AccountId) {
+        self.assert_contract_owner();
+        self.moderators.remove(&account_id);
+    }
+
+    pub fn moderate_fundraiser(&mut self, fundraiser_id: FundraiserId, moderation_status: ModerationStatus) {
+        self.assert_moderator();
+        let mut fundraiser: Fundraiser = self.fundraisers_by_id.get(&fundraiser_id).expect("Incorrect fundraiser id");
+        assert!(fundraiser.moderation_status != ModerationStatus::TAKEN_DOWN, "Fundraiser has already been taken down");
+
+        fundraiser.moderation_status = moderation_status;
+        self.internal_update_fundraiser(fundraiser_id, &fundraiser);
+        self.reported_fundraisers.remove(&fundraiser_id);
+    }
+
+    //removes the fundraiser from the review queue, the reports themselves are kept
+    pub fn dismiss_fundraiser_reports(&mut self, fundraiser_id: FundraiserId) {
+        self.assert_moderator();
+        self.reported_fundraisers.remove(&fundraiser_id);
+    }
+
+    /*
+        donors of a taken down fundraiser get back what they have paid, donations made on behalf of others included.
+        When part of the funds was already paid out to the beneficiaries, every donor gets the same proportion of what is left.
+    */
+    pub fn claim_refund(&mut self, fundraiser_id: FundraiserId) -> U128 {
+        let mut fundraiser: Fundraiser = self.fundraisers_by_id.get(&fundraiser_id).expect("Incorrect fundraiser id");
+        assert_eq!(fundraiser.moderation_status, ModerationStatus::TAKEN_DOWN, "Refunds are only available for taken down fundraisers");
+
+        let donor_id = env::predecessor_account_id();
+        let donated = self.donations_paid_by.remove(&(fundraiser_id, donor_id.clone())).expect("Nothing to refund");
+
+        let withdrawn = self.withdrawn_by_fundraiser.get(&fundraiser_id).unwrap_or(0);
+        let remaining = fundraiser.total_collected.0.saturating_sub(withdrawn);
+        let refund = if withdrawn == 0 { donated } else { mul_div(donated, remaining, fundraiser.total_collected.0) };
+        if withdrawn > 0 {
+            self.withdrawn_by_fundraiser.insert(&fundraiser_id, &(withdrawn - (donated - refund)));
+        }
+        fundraiser.total_collected = U128(fundraiser.total_collected.0 - donated);
+        self.internal_update_fundraiser(fundraiser_id, &fundraiser);
+
+        if refund > 0 {
+            Promise::new(donor_id).transfer(NearToken::from_yoctonear(refund)).detach();
+        }
+        U128(refund)
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::accounts;
+    use near_sdk::testing_env;
+    use crate::test_helpers::{get_context, init_with_fundraiser, STORAGE_DEPOSIT};
+
+    fn report(contract: &mut Contract, reporter_id: AccountId, fundraiser_id: FundraiserId) {
+        testing_env!(get_context(reporter_id)
+            .storage_usage(env::storage_usage())
+            .attached_deposit(NearToken::from_yoctonear(STORAGE_DEPOSIT))
+            .build());
+        contract.report_fundraiser(fundraiser_id, ReportReason::SCAM, None);
+    }
+
+    #[test]
+    fn report_fundraiser_test() {
+        let (mut contract, fundraiser_id) = init_with_fundraiser(accounts(0));
+        report(&mut contract, accounts(2), fundraiser_id);
+
+        let reports = contract.get_fundraiser_reports(fundraiser_id, None, None).items;
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].reporter_id, accounts(2));
+        assert_eq!(contract.get_reported_fundraisers(None, None).items.len(), 1);
+    }
+
+    #[test]
+    fn reported_allowlist_fundraiser_test() {
+        let (mut contract, fundraiser_id) = init_with_fundraiser(accounts(0));
+        contract.set_fundraiser_visibility(fundraiser_id, FundraiserVisibility::ALLOWLIST);
+        report(&mut contract, accounts(0), fundraiser_id);
+        assert!(contract.get_reported_fundraisers(None, None).items.is_empty());
+
+        testing_env!(get_context(accounts(0)).build());
+        contract.add_moderator(accounts(3));
+        testing_env!(get_context(accounts(3)).build());
+        assert!(contract.get_fundraiser_for_account(fundraiser_id).is_some());
+    }
+
+    #[test]
+    #[should_panic(expected = "You have already reported this fundraiser")]
+    fn report_fundraiser_twice_test() {
+        let (mut contract, fundraiser_id) = init_with_fundraiser(accounts(0));
+        report(&mut contract, accounts(2), fundraiser_id);
+        report(&mut contract, accounts(2), fundraiser_id);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only moderators can call this method")]
+    fn moderate_fundraiser_not_moderator_test() {
+        let (mut contract, fundraiser_id) = init_with_fundraiser(accounts(0));
+        testing_env!(get_context(accounts(2)).build());
+        contract.moderate_fundraiser(fundraiser_id, ModerationStatus::HIDDEN);
+    }
+
+    #[test]
+    fn hidden_fundraiser_is_not_listed_test() {
+        let (mut contract, fundraiser_id) = init_with_fundraiser(accounts(0));
+        report(&mut contract, accounts(2), fundraiser_id);
+
+        testing_env!(get_context(accounts(0)).build());
+        contract.add_moderator(accounts(3));
+        testing_env!(get_context(accounts(3)).build());
+        contract.moderate_fundraiser(fundraiser_id, ModerationStatus::HIDDEN);
+
+        assert_eq!(contract.get_all_fundraisers(None, None, None, None, None, None).items.len(), 0);
+        assert_eq!(contract.get_reported_fundraisers(None, None).items.len(), 0);
+        assert!(contract.get_fundraiser_by_id(fundraiser_id).is_some());
+    }
+
+    #[test]
+    #[should_panic(expected = "Fundraiser is not accepting donations")]
+    fn donate_to_hidden_fundraiser_test() {
+        let (mut contract, fundraiser_id) = init_with_fundraiser(accounts(0));
+        contract.moderate_fundraiser(fundraiser_id, ModerationStatus::HIDDEN);
+
+        testing_env!(get_context(accounts(2)).attached_deposit(NearToken::from_yoctonear(1_000 * MIN_DONATION)).build());
+        contract.donate_to_fundraiser(fundraiser_id, None, None);
+    }
+
+    #[test]
+    fn claim_refund_test() {
+        let (mut contract, fundraiser_id) = init_with_fundraiser(accounts(0));
+        testing_env!(get_context(accounts(2)).attached_deposit(NearToken::from_yoctonear(1_000 * MIN_DONATION)).build());
+        contract.donate_to_fundraiser(fundraiser_id, None, None);
+        testing_env!(get_context(accounts(2)).attached_deposit(NearToken::from_yoctonear(500 * MIN_DONATION)).build());
+        contract.donate_to_fundraiser(fundraiser_id, None, None);
+
+        testing_env!(get_context(accounts(0)).build());
+        contract.moderate_fundraiser(fundraiser_id, ModerationStatus::TAKEN_DOWN);
+
+        testing_env!(get_context(accounts(2)).build());
+        assert_eq!(contract.claim_refund(fundraiser_id), U128(1_500 * MIN_DONATION));
+    }
+
+    #[test]
+    #[should_panic(expected = "Nothing to refund")]
+    fn claim_refund_on_behalf_test() {
+        let (mut contract, fundraiser_id) = init_with_fundraiser(accounts(0));
+        testing_env!(get_context(accounts(2)).attached_deposit(NearToken::from_yoctonear(10 * MIN_DONATION)).build());
+        contract.donate_to_fundraiser(fundraiser_id, Some(accounts(3)), None);
+        testing_env!(get_context(accounts(0)).build());
+        contract.moderate_fundraiser(fundraiser_id, ModerationStatus::TAKEN_DOWN);
+
+        //the refund goes to the account that paid
+        testing_env!(get_context(accounts(2)).build());
+        assert_eq!(contract.claim_refund(fundraiser_id), U128(10 * MIN_DONATION));
+        testing_env!(get_context(accounts(3)).build());
+        contract.claim_refund(fundraiser_id);
+    }
+
+    #[test]
+    fn claim_refund_after_payout_test() {
+        let (mut contract, fundraiser_id) = init_with_fundraiser(accounts(0));
+        testing_env!(get_context(accounts(2)).attached_deposit(NearToken::from_yoctonear(20 * MIN_DONATION)).build());
+        contract.donate_to_fundraiser(fundraiser_id, None, None);
+        testing_env!(get_context(accounts(3)).attached_deposit(NearToken::from_yoctonear(60 * MIN_DONATION)).build());
+        contract.donate_to_fundraiser(fundraiser_id, None, None);
+
+        testing_env!(get_context(accounts(0)).build());
+        contract.withdraw_fundraiser_funds(fundraiser_id).detach();
+        testing_env!(get_context(accounts(4)).attached_deposit(NearToken::from_yoctonear(80 * MIN_DONATION)).build());
+        contract.donate_to_fundraiser(fundraiser_id, None, None);
+
+        testing_env!(get_context(accounts(0)).build());
+        contract.moderate_fundraiser(fundraiser_id, ModerationStatus::TAKEN_DOWN);
+
+        //half of the 160 collected was paid out
+        testing_env!(get_context(accounts(4)).build());
+        assert_eq!(contract.claim_refund(fundraiser_id), U128(40 * MIN_DONATION));
+        testing_env!(get_context(accounts(3)).build());
+        assert_eq!(contract.claim_refund(fundraiser_id), U128(30 * MIN_DONATION));
+        assert_eq!(contract.get_withdrawable_amount(fundraiser_id), U128(10 * MIN_DONATION));
+    }
+}
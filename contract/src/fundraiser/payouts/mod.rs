@@ -0,0 +1,223 @@
+// Payout of the collected funds, split between the beneficiaries of the fundraiser.
+
+use near_sdk::Gas;
+use crate::*;
+
+//max number of beneficiaries of one fundraiser, bounds the gas used by a payout
+pub const MAX_BENEFICIARIES: usize = 10;
+//shares are expressed in basis points, the shares of a fundraiser add up to PAYOUT_SHARE_BASE
+pub const PAYOUT_SHARE_BASE: u32 = 10_000;
+const GAS_FOR_RESOLVE_PAYOUT: Gas = Gas::from_tgas(10);
+
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct Beneficiary {
+    pub account_id: AccountId,
+    // share of every payout, in basis points
+    pub share: u32,
+}
+
+//splits the amount according to the shares, the rounding remainder goes to the first beneficiary
+fn split_payout(amount: u128, beneficiaries: &[Beneficiary]) -> Vec<(AccountId, U128)> {
+    let mut payouts: Vec<(AccountId, U128)> = beneficiaries.iter()
+        .map(|beneficiary| {
+            (beneficiary.account_id.clone(), U128(mul_div(amount, u128::from(beneficiary.share), u128::from(PAYOUT_SHARE_BASE))))
+        })
+        .collect();
+    let paid: u128 = payouts.iter().map(|(_, payout)| payout.0).sum();
+    payouts[0].1 = U128(payouts[0].1.0 + amount - paid);
+    payouts
+}
+
+#[near_bindgen]
+impl Contract {
+    /*
+        replaces the beneficiaries of the fundraiser, their shares must add up to 100%.
+        An empty list makes the owner the only beneficiary again. Applies to the payouts made from now on.
+    */
+    #[payable]
+    pub fn set_beneficiaries(&mut self, fundraiser_id: FundraiserId, beneficiaries: Vec<Beneficiary>) {
+        let fundraiser: Fundraiser = self.fundraisers_by_id.get(&fundraiser_id).expect("Incorrect fundraiser id");
+        self.assert_fundraiser_permission(fundraiser_id, &fundraiser, TeamPermission::MANAGE_PAYOUTS, "set the beneficiaries");
+        assert!(beneficiaries.len() <= MAX_BENEFICIARIES, "Abort. Too many beneficiaries, max is {}", MAX_BENEFICIARIES);
+        for (i, beneficiary) in beneficiaries.iter().enumerate() {
+            assert!(beneficiary.share > 0, "Abort. Beneficiary share must be positive");
+            assert!(
+                beneficiaries[..i].iter().all(|other| other.account_id != beneficiary.account_id),
+                "Abort. Beneficiary {} is listed twice", beneficiary.account_id
+            );
+        }
+
+        let initial_storage_usage = env::storage_usage();
+        if beneficiaries.is_empty() {
+            self.beneficiaries_by_fundraiser.remove(&fundraiser_id);
+        } else {
+            let total_share: u32 = beneficiaries.iter().map(|beneficiary| beneficiary.share).sum();
+            assert_eq!(total_share, PAYOUT_SHARE_BASE, "Abort. Beneficiary shares must add up to {}", PAYOUT_SHARE_BASE);
+            self.beneficiaries_by_fundraiser.insert(&fundraiser_id, &beneficiaries);
+        }
+        refund_deposit(env::storage_usage().saturating_sub(initial_storage_usage));
+    }
+
+    //the owner gets everything when the fundraiser has no beneficiaries of its own
+    pub fn get_beneficiaries(&self, fundraiser_id: FundraiserId) -> Vec<Beneficiary> {
+        let fundraiser: Fundraiser = self.fundraisers_by_id.get(&fundraiser_id).expect("Incorrect fundraiser id");
+        if !self.can_view_fundraiser(fundraiser_id, None) {
+            return vec![];
+        }
+        self.internal_beneficiaries(fundraiser_id, &fundraiser)
+    }
+
+    //collected funds that weren't paid out yet and, for fundraisers with a vesting schedule, have already vested
+    pub fn get_withdrawable_amount(&self, fundraiser_id: FundraiserId) -> U128 {
+        let fundraiser: Fundraiser = self.fundraisers_by_id.get(&fundraiser_id).expect("Incorrect fundraiser id");
+        let vested = self.internal_vested_amount(fundraiser_id, fundraiser.total_collected.0);
+        U128(vested.saturating_sub(self.withdrawn_by_fundraiser.get(&fundraiser_id).unwrap_or(0)))
+    }
+
+    /*
+        pays the withdrawable funds out to the beneficiaries in one batch of transfers.
+        The owner, team members allowed to request withdrawals and the beneficiaries can call it at any time,
+        anyone can once the fundraiser is over.
+        Transfers that fail are taken back into the withdrawable amount by resolve_payout.
+    */
+    pub fn withdraw_fundraiser_funds(&mut self, fundraiser_id: FundraiserId) -> Promise {
+        self.internal_settle_donations(fundraiser_id, MAX_SETTLEMENTS_PER_CALL);
+        let fundraiser: Fundraiser = self.fundraisers_by_id.get(&fundraiser_id).expect("Incorrect fundraiser id");
+        let caller_id = env::predecessor_account_id();
+        assert!(
+            fundraiser.is_over()
+                || self.has_fundraiser_permission(fundraiser_id, &fundraiser, &caller_id, TeamPermission::REQUEST_WITHDRAWALS)
+                || self.is_beneficiary(fundraiser_id, &fundraiser, &caller_id),
+            "Only the fundraiser owner, its team or a beneficiary can withdraw before the fundraiser is over"
+        );
+        self.internal_payout(fundraiser_id, &fundraiser)
+    }
+
+    //takes the failed transfers of a payout back into the withdrawable amount, returns what was paid out
+    #[private]
+    pub fn resolve_payout(&mut self, fundraiser_id: FundraiserId, payouts: Vec<(AccountId, U128)>) -> U128 {
+        let mut paid: Vec<FundraiserPayoutLog> = vec![];
+        let mut failed: u128 = 0;
+        for (i, (beneficiary_id, amount)) in payouts.into_iter().enumerate() {
+            //transfers don't return any data
+            match env::promise_result_checked(i as u64, 0) {
+                Ok(_) => paid.push(FundraiserPayoutLog { fundraiser_id, beneficiary_id, amount }),
+                Err(_) => failed += amount.0,
+            }
+        }
+
+        if failed > 0 {
+            let withdrawn = self.withdrawn_by_fundraiser.get(&fundraiser_id).unwrap_or(0);
+            self.withdrawn_by_fundraiser.insert(&fundraiser_id, &withdrawn.saturating_sub(failed));
+        }
+        let total_paid: u128 = paid.iter().map(|payout| payout.amount.0).sum();
+        if !paid.is_empty() {
+            EventLog::new(EventLogVariant::FundraiserPayout(paid)).emit();
+        }
+        U128(total_paid)
+    }
+}
+
+impl Contract {
+    pub(crate) fn internal_beneficiaries(&self, fundraiser_id: FundraiserId, fundraiser: &Fundraiser) -> Vec<Beneficiary> {
+        self.beneficiaries_by_fundraiser.get(&fundraiser_id).unwrap_or_else(|| {
+            vec![Beneficiary { account_id: fundraiser.owner_id.clone(), share: PAYOUT_SHARE_BASE }]
+        })
+    }
+
+    pub(crate) fn is_beneficiary(&self, fundraiser_id: FundraiserId, fundraiser: &Fundraiser, account_id: &AccountId) -> bool {
+        self.internal_beneficiaries(fundraiser_id, fundraiser).iter().any(|beneficiary| &beneficiary.account_id == account_id)
+    }
+
+    //pays the withdrawable funds of the fundraiser out to its beneficiaries, see withdraw_fundraiser_funds
+    pub(crate) fn internal_payout(&mut self, fundraiser_id: FundraiserId, fundraiser: &Fundraiser) -> Promise {
+        assert!(fundraiser.moderation_status != ModerationStatus::TAKEN_DOWN, "Funds of taken down fundraisers are refunded to the donors");
+        let amount = self.get_withdrawable_amount(fundraiser_id).0;
+        assert!(amount > 0, "Nothing to withdraw");
+        let withdrawn = self.withdrawn_by_fundraiser.get(&fundraiser_id).unwrap_or(0);
+        self.withdrawn_by_fundraiser.insert(&fundraiser_id, &(withdrawn + amount));
+
+        let payouts: Vec<(AccountId, U128)> = split_payout(amount, &self.internal_beneficiaries(fundraiser_id, fundraiser))
+            .into_iter()
+            .filter(|(_, payout)| payout.0 > 0)
+            .collect();
+        let transfers = payouts.iter()
+            .map(|(account_id, payout)| Promise::new(account_id.clone()).transfer(NearToken::from_yoctonear(payout.0)))
+            .reduce(|batch, transfer| batch.and(transfer))
+            .unwrap();
+        transfers.then(
+            Self::ext(env::current_account_id())
+                .with_static_gas(GAS_FOR_RESOLVE_PAYOUT)
+                .resolve_payout(fundraiser_id, payouts)
+        )
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::{accounts, get_logs};
+    use near_sdk::{testing_env, test_vm_config, PromiseResult, RuntimeFeesConfig};
+    use crate::test_helpers::{get_context, init_with_fundraiser, ONE_NEAR, STORAGE_DEPOSIT};
+
+    fn beneficiary(account_id: AccountId, share: u32) -> Beneficiary {
+        Beneficiary { account_id, share }
+    }
+
+    fn set_beneficiaries(contract: &mut Contract, fundraiser_id: FundraiserId, beneficiaries: Vec<Beneficiary>) {
+        testing_env!(get_context(accounts(1))
+            .storage_usage(env::storage_usage())
+            .attached_deposit(NearToken::from_yoctonear(STORAGE_DEPOSIT))
+            .build());
+        contract.set_beneficiaries(fundraiser_id, beneficiaries);
+    }
+
+    #[test]
+    fn split_payout_test() {
+        let payouts = split_payout(10, &[beneficiary(accounts(1), 3_333), beneficiary(accounts(2), 6_667)]);
+        assert_eq!(payouts, vec![(accounts(1), U128(4)), (accounts(2), U128(6))]);
+    }
+
+    #[test]
+    fn withdraw_fundraiser_funds_test() {
+        let (mut contract, fundraiser_id) = init_with_fundraiser(accounts(1));
+        set_beneficiaries(&mut contract, fundraiser_id, vec![beneficiary(accounts(2), 7_000), beneficiary(accounts(3), 3_000)]);
+        testing_env!(get_context(accounts(4)).attached_deposit(NearToken::from_yoctonear(10 * ONE_NEAR)).build());
+        contract.donate_to_fundraiser(fundraiser_id, None, None);
+
+        testing_env!(get_context(accounts(2)).build());
+        contract.withdraw_fundraiser_funds(fundraiser_id).detach();
+        assert_eq!(contract.get_withdrawable_amount(fundraiser_id), U128(0));
+
+        //the transfer to the second beneficiary failed
+        testing_env!(
+            get_context(accounts(0)).build(),
+            test_vm_config(),
+            RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![PromiseResult::Successful(vec![]), PromiseResult::Failed],
+        );
+        let paid = contract.resolve_payout(fundraiser_id, vec![(accounts(2), U128(7 * ONE_NEAR)), (accounts(3), U128(3 * ONE_NEAR))]);
+        assert_eq!(paid, U128(7 * ONE_NEAR));
+        assert_eq!(contract.get_withdrawable_amount(fundraiser_id), U128(3 * ONE_NEAR));
+        assert!(get_logs().iter().any(|log| log.contains(r#""event":"fundraiser_payout""#)));
+    }
+
+    #[test]
+    #[should_panic(expected = "Abort. Beneficiary shares must add up to 10000")]
+    fn set_beneficiaries_wrong_total_test() {
+        let (mut contract, fundraiser_id) = init_with_fundraiser(accounts(1));
+        set_beneficiaries(&mut contract, fundraiser_id, vec![beneficiary(accounts(2), 7_000), beneficiary(accounts(3), 2_000)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the fundraiser owner, its team or a beneficiary can withdraw before the fundraiser is over")]
+    fn withdraw_fundraiser_funds_not_beneficiary_test() {
+        let (mut contract, fundraiser_id) = init_with_fundraiser(accounts(1));
+        testing_env!(get_context(accounts(4)).attached_deposit(NearToken::from_yoctonear(ONE_NEAR)).build());
+        contract.donate_to_fundraiser(fundraiser_id, None, None);
+        contract.withdraw_fundraiser_funds(fundraiser_id).detach();
+    }
+}
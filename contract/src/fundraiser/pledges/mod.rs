@@ -0,0 +1,290 @@
+// Recurring pledges: donors lock a prepaid balance that keepers turn into periodic donations.
+
+use crate::*;
+
+pub type PledgeId = u64;
+
+//paid to the keeper out of the pledge balance for every executed payment (0.001 NEAR)
+pub const PLEDGE_KEEPER_BOUNTY: u128 = 1_000_000_000_000_000_000_000;
+//shortest allowed period between two payments (1 hour), Unix epoch in milliseconds
+pub const MIN_PLEDGE_PERIOD: u64 = 3_600_000;
+//max number of pledges executed by one process_due_pledges call
+pub const MAX_PLEDGES_PER_CALL: u32 = 50;
+
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct Pledge {
+    pub donor_id: AccountId,
+    pub fundraiser_id: FundraiserId,
+    // donated every period
+    pub amount: U128,
+    // length of a period in milliseconds
+    pub period: u64,
+    // When the next payment is due, Unix epoch in milliseconds
+    pub next_payment_at: u64,
+    // no payments are made after this moment, Unix epoch in milliseconds
+    pub ends_at: u64,
+    // prepaid funds left for the next payments and keeper bounties
+    pub balance: U128,
+    pub payments_made: u64,
+    // false once the pledge ended, ran out of balance or the fundraiser stopped accepting donations
+    pub active: bool,
+}
+
+impl Pledge {
+    fn cost_per_payment(&self) -> u128 {
+        self.amount.0 + PLEDGE_KEEPER_BOUNTY
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct JsonPledge {
+    pub pledge_id: PledgeId,
+    pub pledge: Pledge,
+}
+
+#[near_bindgen]
+impl Contract {
+    /*
+        sets up a recurring pledge. The attached deposit, minus the storage used by the pledge,
+        becomes the prepaid balance. The first payment is due right away.
+    */
+    #[payable]
+    pub fn create_pledge(&mut self, fundraiser_id: FundraiserId, amount: U128, period: u64, ends_at: u64) -> PledgeId {
+        let fundraiser: Fundraiser = self.fundraisers_by_id.get(&fundraiser_id).expect("Incorrect fundraiser id");
+        assert!(fundraiser.is_accepting_donations(), "Fundraiser is not accepting donations");
+        assert!(
+            self.is_allowed_on_fundraiser(fundraiser_id, &fundraiser, &env::predecessor_account_id()),
+            "Only allowlisted accounts can donate to this fundraiser"
+        );
+        let min_donation = self.internal_min_donation(fundraiser_id);
+        assert!(amount.0 >= min_donation, "Abort. Amount is below the min donation of {} yoctoNEAR", min_donation);
+        assert!(period >= MIN_PLEDGE_PERIOD, "Abort. Period must be at least 1 hour");
+        let now = env::block_timestamp_ms();
+        assert!(ends_at > now, "Abort. End date is in the past");
+
+        let initial_storage_usage = env::storage_usage();
+        let donor_id = env::predecessor_account_id();
+
+        self.pledge_counter += 1;
+        let pledge_id: PledgeId = self.pledge_counter;
+        let mut pledge = Pledge {
+            donor_id: donor_id.clone(),
+            fundraiser_id,
+            amount,
+            period,
+            next_payment_at: now,
+            ends_at,
+            balance: U128(0),
+            payments_made: 0,
+            active: true,
+        };
+        self.pledges_by_id.insert(&pledge_id, &pledge);
+        self.pledges_by_due_date.insert(&(now, pledge_id), &());
+
+        let mut pledges_set = self.pledges_per_donor.get(&donor_id).unwrap_or_else(|| {
+            UnorderedSet::new(
+                StorageKey::PledgesPerDonorInner { account_id_hash: hash_account_id(&donor_id) }
+            )
+        });
+        pledges_set.insert(&pledge_id);
+        self.pledges_per_donor.insert(&donor_id, &pledges_set);
+
+        //whatever is left after paying for the storage is locked as the balance
+        let storage_cost = env::storage_byte_cost().as_yoctonear() * u128::from(env::storage_usage() - initial_storage_usage);
+        let deposit = env::attached_deposit().as_yoctonear();
+        assert!(deposit >= storage_cost + pledge.cost_per_payment(), "Must attach at least {} yoctoNEAR to cover storage and the first payment", storage_cost + pledge.cost_per_payment());
+        pledge.balance = U128(deposit - storage_cost);
+        self.pledges_by_id.insert(&pledge_id, &pledge);
+
+        pledge_id
+    }
+
+    //adds the attached deposit to the balance of the pledge and resumes it if it ran dry
+    #[payable]
+    pub fn top_up_pledge(&mut self, pledge_id: PledgeId) {
+        let mut pledge = self.pledges_by_id.get(&pledge_id).expect("Incorrect pledge id");
+        assert_eq!(env::predecessor_account_id(), pledge.donor_id, "Only the donor can top up the pledge");
+        pledge.balance = U128(pledge.balance.0 + env::attached_deposit().as_yoctonear());
+
+        let now = env::block_timestamp_ms();
+        if !pledge.active && pledge.balance.0 >= pledge.cost_per_payment() && pledge.ends_at > now {
+            let fundraiser_open = self.fundraisers_by_id.get(&pledge.fundraiser_id).is_some_and(|fundraiser| fundraiser.is_accepting_donations());
+            if fundraiser_open {
+                pledge.active = true;
+                pledge.next_payment_at = std::cmp::max(pledge.next_payment_at, now);
+                self.pledges_by_due_date.insert(&(pledge.next_payment_at, pledge_id), &());
+            }
+        }
+        self.pledges_by_id.insert(&pledge_id, &pledge);
+    }
+
+    //stops the pledge and sends the remaining balance, plus the released storage, back to the donor
+    pub fn cancel_pledge(&mut self, pledge_id: PledgeId) -> U128 {
+        let pledge = self.pledges_by_id.get(&pledge_id).expect("Incorrect pledge id");
+        assert_eq!(env::predecessor_account_id(), pledge.donor_id, "Only the donor can cancel the pledge");
+
+        let initial_storage_usage = env::storage_usage();
+        self.pledges_by_id.remove(&pledge_id);
+        if pledge.active {
+            self.pledges_by_due_date.remove(&(pledge.next_payment_at, pledge_id));
+        }
+        let mut pledges_set = self.pledges_per_donor.get(&pledge.donor_id).expect("Incorrect pledge id");
+        pledges_set.remove(&pledge_id);
+        if pledges_set.is_empty() {
+            self.pledges_per_donor.remove(&pledge.donor_id);
+        } else {
+            self.pledges_per_donor.insert(&pledge.donor_id, &pledges_set);
+        }
+        let released_storage = env::storage_byte_cost().as_yoctonear() * u128::from(initial_storage_usage.saturating_sub(env::storage_usage()));
+
+        let refund = pledge.balance.0 + released_storage;
+        if refund > 0 {
+            Promise::new(pledge.donor_id).transfer(NearToken::from_yoctonear(refund)).detach();
+        }
+        U128(refund)
+    }
+
+    /*
+        executes up to `limit` pledges whose payment is due, through the same path as donate_to_fundraiser.
+        Pledge payments skip the cooling-off period, the pledge itself can be cancelled at any time.
+        The caller receives PLEDGE_KEEPER_BOUNTY for every executed payment. Returns the number of payments made.
+    */
+    pub fn process_due_pledges(&mut self, limit: Option<u32>) -> u32 {
+        let now = env::block_timestamp_ms();
+        let limit = std::cmp::min(limit.unwrap_or(MAX_PLEDGES_PER_CALL), MAX_PLEDGES_PER_CALL);
+        let due: Vec<(u64, PledgeId)> = self.pledges_by_due_date.iter()
+            .map(|(key, _)| key)
+            .take_while(|(due_at, _)| *due_at <= now)
+            .take(limit as usize)
+            .collect();
+
+        let mut payments_made: u32 = 0;
+        for (due_at, pledge_id) in due {
+            self.pledges_by_due_date.remove(&(due_at, pledge_id));
+            let mut pledge = self.pledges_by_id.get(&pledge_id).unwrap();
+
+            //pledges stop once the donation limits of the fundraiser don't leave room for a full payment
+            let fundraiser_open = self.fundraisers_by_id.get(&pledge.fundraiser_id).is_some_and(|fundraiser| {
+                fundraiser.is_accepting_donations()
+                    && self.is_allowed_on_fundraiser(pledge.fundraiser_id, &fundraiser, &pledge.donor_id)
+                    && self.internal_donation_room(pledge.fundraiser_id, &fundraiser, &pledge.donor_id) >= pledge.amount.0
+            });
+            if fundraiser_open && due_at <= pledge.ends_at && pledge.balance.0 >= pledge.cost_per_payment() {
+                pledge.balance = U128(pledge.balance.0 - pledge.cost_per_payment());
+                pledge.payments_made += 1;
+                payments_made += 1;
+                self.internal_donate(pledge.fundraiser_id, &pledge.donor_id, &pledge.donor_id, pledge.amount.0, env::block_timestamp_ms(), None);
+                pledge.next_payment_at = due_at + pledge.period;
+            }
+
+            pledge.active = fundraiser_open
+                && pledge.next_payment_at > due_at
+                && pledge.next_payment_at <= pledge.ends_at
+                && pledge.balance.0 >= pledge.cost_per_payment();
+            if pledge.active {
+                self.pledges_by_due_date.insert(&(pledge.next_payment_at, pledge_id), &());
+            }
+            self.pledges_by_id.insert(&pledge_id, &pledge);
+        }
+
+        if payments_made > 0 {
+            Promise::new(env::predecessor_account_id()).transfer(NearToken::from_yoctonear(PLEDGE_KEEPER_BOUNTY * u128::from(payments_made))).detach();
+        }
+        payments_made
+    }
+
+    pub fn get_pledge(&self, pledge_id: PledgeId) -> Option<JsonPledge> {
+        self.pledges_by_id.get(&pledge_id).map(|pledge| JsonPledge { pledge_id, pledge })
+    }
+
+    //Query for all the pledges of a donor
+    pub fn get_pledges_by_donor(&self, donor_id: AccountId, from_index: Option<U128>, limit: Option<u64>) -> Vec<JsonPledge> {
+        let pledges = if let Some(pledges_set) = self.pledges_per_donor.get(&donor_id) {
+            pledges_set
+        } else {
+            return vec![];
+        };
+        pledges.iter()
+            .skip(page_start(from_index))
+            .take(page_limit(limit))
+            .filter_map(|pledge_id| self.get_pledge(pledge_id))
+            .collect()
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::accounts;
+    use near_sdk::testing_env;
+    use crate::test_helpers::{get_context, init_with_fundraiser, ONE_NEAR};
+
+    const DAY: u64 = 86_400_000;
+
+    fn at(day: u64) -> u64 {
+        //block timestamps are in nanoseconds
+        day * DAY * 1_000_000
+    }
+
+    fn create_pledge(contract: &mut Contract, fundraiser_id: FundraiserId, deposit: u128) -> PledgeId {
+        testing_env!(get_context(accounts(2))
+            .storage_usage(env::storage_usage())
+            .attached_deposit(NearToken::from_yoctonear(deposit))
+            .build());
+        contract.create_pledge(fundraiser_id, U128(ONE_NEAR), 30 * DAY, 90 * DAY)
+    }
+
+    fn process(contract: &mut Contract, day: u64) -> u32 {
+        testing_env!(get_context(accounts(3)).block_timestamp(at(day)).build());
+        contract.process_due_pledges(None)
+    }
+
+    #[test]
+    fn process_due_pledges_test() {
+        let (mut contract, fundraiser_id) = init_with_fundraiser(accounts(0));
+        let pledge_id = create_pledge(&mut contract, fundraiser_id, 5 * ONE_NEAR);
+
+        assert_eq!(process(&mut contract, 0), 1);
+        //nothing is due until the next period starts
+        assert_eq!(process(&mut contract, 10), 0);
+        assert_eq!(process(&mut contract, 30), 1);
+        assert_eq!(process(&mut contract, 60), 1);
+        assert_eq!(process(&mut contract, 90), 1);
+        //the end date has been reached
+        assert_eq!(process(&mut contract, 120), 0);
+
+        let pledge = contract.get_pledge(pledge_id).unwrap().pledge;
+        assert_eq!(pledge.payments_made, 4);
+        assert!(!pledge.active);
+        assert_eq!(contract.fundraisers_by_id.get(&fundraiser_id).unwrap().total_collected, U128(4 * ONE_NEAR));
+    }
+
+    #[test]
+    fn pledge_runs_out_of_balance_test() {
+        let (mut contract, fundraiser_id) = init_with_fundraiser(accounts(0));
+        let pledge_id = create_pledge(&mut contract, fundraiser_id, 2 * ONE_NEAR + ONE_NEAR / 2);
+
+        assert_eq!(process(&mut contract, 0), 1);
+        assert_eq!(process(&mut contract, 30), 1);
+        assert_eq!(process(&mut contract, 60), 0);
+        assert!(!contract.get_pledge(pledge_id).unwrap().pledge.active);
+
+        testing_env!(get_context(accounts(2)).block_timestamp(at(61)).build());
+        let refund = contract.cancel_pledge(pledge_id);
+        assert!(refund.0 > 0 && refund.0 < ONE_NEAR / 2);
+        assert!(contract.get_pledge(pledge_id).is_none());
+        assert!(contract.get_pledges_by_donor(accounts(2), None, None).is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the donor can cancel the pledge")]
+    fn cancel_pledge_not_donor_test() {
+        let (mut contract, fundraiser_id) = init_with_fundraiser(accounts(0));
+        let pledge_id = create_pledge(&mut contract, fundraiser_id, 5 * ONE_NEAR);
+        testing_env!(get_context(accounts(3)).build());
+        contract.cancel_pledge(pledge_id);
+    }
+}
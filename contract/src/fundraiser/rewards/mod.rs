@@ -0,0 +1,290 @@
+// Reward tiers: NFT perks minted to donors once their donations to a fundraiser cross a threshold.
+
+use crate::*;
+
+//max number of tiers per fundraiser, bounds the gas used by a donation
+pub const MAX_REWARD_TIERS: u64 = 20;
+//storage prepaid by the fundraiser owner for every edition of a tier, 0.02 NEAR covers 2000 bytes
+pub const REWARD_EDITION_STORAGE_COST: u128 = 20_000_000_000_000_000_000_000;
+const MAX_REWARD_TITLE_LENGTH: usize = 200;
+const MAX_REWARD_DESCRIPTION_LENGTH: usize = 500;
+const MAX_REWARD_MEDIA_LENGTH: usize = 300;
+
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct RewardTier {
+    // position of the tier on the fundraiser, starting from 0
+    pub id: u64,
+    // total a donor has to give to the fundraiser to receive the reward
+    pub min_donation: U128,
+    // stretch goal, the tier is only available once the fundraiser collected this much
+    pub unlocks_at: Option<U128>,
+    // template of the minted tokens
+    pub title: String,
+    pub description: Option<String>,
+    pub media: Option<String>,
+    pub media_hash: Option<Base64VecU8>,
+    pub max_supply: u64,
+    pub minted: u64,
+}
+
+impl RewardTier {
+    fn is_unlocked(&self, total_collected: u128) -> bool {
+        self.unlocks_at.is_none_or(|unlocks_at| total_collected >= unlocks_at.0)
+    }
+
+    //metadata of the given edition of the reward
+    fn edition_metadata(&self, fundraiser_id: FundraiserId, edition: u64) -> TokenMetadata {
+        TokenMetadata {
+            title: Some(format!("{} #{}", self.title, edition)),
+            description: self.description.clone(),
+            media: self.media.clone(),
+            media_hash: self.media_hash.clone(),
+            copies: Some(self.max_supply),
+            issued_at: None,
+            expires_at: None,
+            starts_at: None,
+            updated_at: None,
+            extra: Some(format!(r#"{{"fundraiser_id":{},"tier_id":{},"edition":{}}}"#, fundraiser_id, self.id, edition)),
+            reference: None,
+            reference_hash: None,
+        }
+    }
+}
+
+//arguments of add_reward_tier, see RewardTier
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct NewRewardTier {
+    pub min_donation: U128,
+    pub unlocks_at: Option<U128>,
+    pub title: String,
+    pub description: Option<String>,
+    pub media: Option<String>,
+    pub media_hash: Option<Base64VecU8>,
+    pub max_supply: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct JsonRewardTier {
+    pub tier: RewardTier,
+    // the stretch goal of the tier has been reached
+    pub unlocked: bool,
+}
+
+#[near_bindgen]
+impl Contract {
+    /*
+        adds a reward tier to the fundraiser. The attached deposit must cover the storage of the tier
+        plus REWARD_EDITION_STORAGE_COST for every edition, anything above that is refunded.
+    */
+    #[payable]
+    pub fn add_reward_tier(&mut self, fundraiser_id: FundraiserId, tier: NewRewardTier) -> u64 {
+        let NewRewardTier { min_donation, unlocks_at, title, description, media, media_hash, max_supply } = tier;
+        let fundraiser: Fundraiser = self.fundraisers_by_id.get(&fundraiser_id).expect("Incorrect fundraiser id");
+        self.assert_fundraiser_permission(fundraiser_id, &fundraiser, TeamPermission::MANAGE_TIERS, "add reward tiers");
+        assert!(min_donation.0 > 0, "Abort. Min donation must be greater than 0");
+        assert!(!title.is_empty(), "Abort. Title is empty");
+        assert!(title.len() <= MAX_REWARD_TITLE_LENGTH, "Abort. Title is longer then 200 characters");
+        assert!(description.as_ref().is_none_or(|description| description.len() <= MAX_REWARD_DESCRIPTION_LENGTH), "Abort. Description is longer then 500 characters");
+        assert!(media.as_ref().is_none_or(|media| media.len() <= MAX_REWARD_MEDIA_LENGTH), "Abort. Media is longer then 300 characters");
+        assert!(max_supply > 0, "Abort. Max supply must be greater than 0");
+
+        let initial_storage_usage = env::storage_usage();
+
+        let mut tiers = self.reward_tiers.get(&fundraiser_id).unwrap_or_else(|| {
+            Vector::new(StorageKey::RewardTiersInner { fundraiser_id })
+        });
+        assert!(tiers.len() < MAX_REWARD_TIERS, "Abort. A fundraiser can have at most 20 reward tiers");
+        let tier_id = tiers.len();
+        let tier = RewardTier {
+            id: tier_id,
+            min_donation,
+            unlocks_at,
+            title,
+            description,
+            media,
+            media_hash,
+            max_supply,
+            minted: 0,
+        };
+        //the editions are minted during donations, so their metadata is checked now
+        self.assert_valid_token_metadata(&tier.edition_metadata(fundraiser_id, max_supply));
+        tiers.push(&tier);
+        self.reward_tiers.insert(&fundraiser_id, &tiers);
+
+        let storage_cost = env::storage_byte_cost().as_yoctonear() * u128::from(env::storage_usage() - initial_storage_usage);
+        let required = storage_cost + REWARD_EDITION_STORAGE_COST * u128::from(max_supply);
+        let deposit = env::attached_deposit().as_yoctonear();
+        assert!(deposit >= required, "Must attach {} yoctoNEAR to cover the tier and the storage of its editions", required);
+        if deposit - required > 1 {
+            Promise::new(env::predecessor_account_id()).transfer(NearToken::from_yoctonear(deposit - required)).detach();
+        }
+
+        tier_id
+    }
+
+    //stops minting the tier, the storage prepaid for the editions that were not minted goes back to the owner
+    pub fn close_reward_tier(&mut self, fundraiser_id: FundraiserId, tier_id: u64) -> U128 {
+        let fundraiser: Fundraiser = self.fundraisers_by_id.get(&fundraiser_id).expect("Incorrect fundraiser id");
+        self.assert_fundraiser_permission(fundraiser_id, &fundraiser, TeamPermission::MANAGE_TIERS, "close reward tiers");
+        let mut tiers = self.reward_tiers.get(&fundraiser_id).expect("Incorrect reward tier id");
+        let mut tier = tiers.get(tier_id).expect("Incorrect reward tier id");
+
+        let refund = REWARD_EDITION_STORAGE_COST * u128::from(tier.max_supply - tier.minted);
+        tier.max_supply = tier.minted;
+        tiers.replace(tier_id, &tier);
+        if refund > 0 {
+            Promise::new(fundraiser.owner_id).transfer(NearToken::from_yoctonear(refund)).detach();
+        }
+        U128(refund)
+    }
+
+    pub fn get_reward_tiers(&self, fundraiser_id: FundraiserId) -> Vec<JsonRewardTier> {
+        let total_collected = match self.fundraisers_by_id.get(&fundraiser_id) {
+            Some(fundraiser) if self.can_view_fundraiser(fundraiser_id, None) => fundraiser.total_collected.0,
+            _ => return vec![],
+        };
+        self.reward_tiers.get(&fundraiser_id).map_or(vec![], |tiers| {
+            tiers.iter()
+                .map(|tier| JsonRewardTier { unlocked: tier.is_unlocked(total_collected), tier })
+                .collect()
+        })
+    }
+}
+
+impl Contract {
+    //everything the donor has given to the fundraiser so far
+    //what the account paid to the fundraiser, for itself or on behalf of others
+    pub(crate) fn internal_paid_total(&self, fundraiser_id: FundraiserId, account_id: &AccountId) -> u128 {
+        self.donations_paid_by.get(&(fundraiser_id, account_id.clone())).unwrap_or(0)
+    }
+
+    pub(crate) fn internal_donor_total(&self, fundraiser_id: FundraiserId, donor_id: &AccountId) -> u128 {
+        self.fundraisers_donations.get(&fundraiser_id)
+            .and_then(|donations| donations.get(donor_id))
+            .map_or(0, |donations_of_donor| donations_of_donor.iter().sum())
+    }
+
+    /*
+        mints the reward of every unlocked tier whose threshold the donor crossed, going from
+        `previous_total` to `new_total`. The storage of the tokens was prepaid with the tier.
+    */
+    pub(crate) fn internal_mint_rewards(&mut self, fundraiser_id: FundraiserId, donor_id: &AccountId, previous_total: u128, new_total: u128) {
+        let mut tiers = match self.reward_tiers.get(&fundraiser_id) {
+            Some(tiers) => tiers,
+            None => return,
+        };
+        let total_collected = self.fundraisers_by_id.get(&fundraiser_id).unwrap().total_collected.0;
+
+        for tier_id in 0..tiers.len() {
+            let mut tier = tiers.get(tier_id).unwrap();
+            let crossed = previous_total < tier.min_donation.0 && new_total >= tier.min_donation.0;
+            if !crossed || !tier.is_unlocked(total_collected) || tier.minted >= tier.max_supply {
+                continue;
+            }
+            tier.minted += 1;
+            let token_id = self.internal_next_token_id("reward");
+            self.internal_mint(token_id.clone(), donor_id.clone(), tier.edition_metadata(fundraiser_id, tier.minted));
+            self.internal_add_token_to_fundraiser(fundraiser_id, &token_id);
+            tiers.replace(tier_id, &tier);
+        }
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::{accounts, get_logs};
+    use near_sdk::testing_env;
+    use crate::test_helpers::{get_context, init_with_fundraiser, donate, ONE_NEAR};
+
+    fn add_tier(contract: &mut Contract, fundraiser_id: FundraiserId, min_donation: u128, unlocks_at: Option<u128>, max_supply: u64) -> u64 {
+        testing_env!(get_context(accounts(0))
+            .storage_usage(env::storage_usage())
+            .attached_deposit(NearToken::from_yoctonear(ONE_NEAR))
+            .build());
+        contract.add_reward_tier(fundraiser_id, new_tier(min_donation, unlocks_at, max_supply))
+    }
+
+    fn new_tier(min_donation: u128, unlocks_at: Option<u128>, max_supply: u64) -> NewRewardTier {
+        NewRewardTier {
+            min_donation: U128(min_donation),
+            unlocks_at: unlocks_at.map(U128),
+            title: "Thank you".to_string(),
+            description: None,
+            media: None,
+            media_hash: None,
+            max_supply,
+        }
+    }
+
+    #[test]
+    fn reward_minted_when_threshold_crossed_test() {
+        let (mut contract, fundraiser_id) = init_with_fundraiser(accounts(0));
+        add_tier(&mut contract, fundraiser_id, 2 * ONE_NEAR, None, 10);
+
+        donate(&mut contract, accounts(2), fundraiser_id, ONE_NEAR);
+        assert_eq!(contract.nft_supply_for_owner(accounts(2)), U128(0));
+        donate(&mut contract, accounts(2), fundraiser_id, ONE_NEAR);
+        assert_eq!(contract.nft_supply_for_owner(accounts(2)), U128(1));
+        //the threshold is only crossed once
+        donate(&mut contract, accounts(2), fundraiser_id, ONE_NEAR);
+        assert_eq!(contract.nft_supply_for_owner(accounts(2)), U128(1));
+
+        let token = contract.nft_tokens_for_owner(accounts(2), None, None).pop().unwrap();
+        assert_eq!(token.owner_id, accounts(2));
+        assert_eq!(token.metadata.copies, Some(10));
+        assert_eq!(token.metadata.extra, Some(format!(r#"{{"fundraiser_id":{},"tier_id":0,"edition":1}}"#, fundraiser_id)));
+    }
+
+    #[test]
+    fn reward_minted_to_gift_recipient_test() {
+        let (mut contract, fundraiser_id) = init_with_fundraiser(accounts(0));
+        add_tier(&mut contract, fundraiser_id, ONE_NEAR, None, 10);
+
+        testing_env!(get_context(accounts(2)).attached_deposit(NearToken::from_yoctonear(ONE_NEAR)).build());
+        contract.donate_to_fundraiser(fundraiser_id, Some(accounts(3)), Some("In memory of Grandpa Joe".to_string()));
+        assert_eq!(contract.nft_supply_for_owner(accounts(2)), U128(0));
+        assert_eq!(contract.nft_supply_for_owner(accounts(3)), U128(1));
+        assert_eq!(contract.internal_donor_total(fundraiser_id, &accounts(2)), 0);
+        assert_eq!(contract.internal_donor_total(fundraiser_id, &accounts(3)), ONE_NEAR);
+        assert!(get_logs().iter().any(|log| log.contains(r#""event":"donation_gift""#) && log.contains("In memory of Grandpa Joe")));
+    }
+
+    #[test]
+    fn stretch_goal_tier_test() {
+        let (mut contract, fundraiser_id) = init_with_fundraiser(accounts(0));
+        add_tier(&mut contract, fundraiser_id, ONE_NEAR, Some(3 * ONE_NEAR), 10);
+
+        donate(&mut contract, accounts(2), fundraiser_id, ONE_NEAR);
+        assert_eq!(contract.nft_supply_for_owner(accounts(2)), U128(0));
+        assert!(!contract.get_reward_tiers(fundraiser_id)[0].unlocked);
+
+        //the donation reaching the stretch goal unlocks the tier
+        donate(&mut contract, accounts(3), fundraiser_id, 2 * ONE_NEAR);
+        assert_eq!(contract.nft_supply_for_owner(accounts(3)), U128(1));
+        assert!(contract.get_reward_tiers(fundraiser_id)[0].unlocked);
+    }
+
+    #[test]
+    fn reward_tier_max_supply_test() {
+        let (mut contract, fundraiser_id) = init_with_fundraiser(accounts(0));
+        add_tier(&mut contract, fundraiser_id, ONE_NEAR, None, 1);
+
+        donate(&mut contract, accounts(2), fundraiser_id, ONE_NEAR);
+        donate(&mut contract, accounts(3), fundraiser_id, ONE_NEAR);
+        assert_eq!(contract.nft_supply_for_owner(accounts(3)), U128(0));
+        assert_eq!(contract.get_reward_tiers(fundraiser_id)[0].tier.minted, 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the fundraiser owner or team members with the MANAGE_TIERS permission can add reward tiers")]
+    fn add_reward_tier_not_owner_test() {
+        let (mut contract, fundraiser_id) = init_with_fundraiser(accounts(0));
+        testing_env!(get_context(accounts(2)).attached_deposit(NearToken::from_yoctonear(ONE_NEAR)).build());
+        contract.add_reward_tier(fundraiser_id, new_tier(ONE_NEAR, None, 1));
+    }
+}
@@ -0,0 +1,380 @@
+// Quadratic funding rounds: a matching pot shared between fundraisers according to how many donors back them.
+
+use crate::*;
+
+pub type FundingRoundId = u64;
+
+//max number of fundraisers taking part in one round, bounds the gas used to close it
+pub const MAX_ROUND_FUNDRAISERS: usize = 50;
+//shares are expressed in basis points
+pub const ROUND_SHARE_BASE: u32 = 10_000;
+//contributions are counted in micro NEAR so that the quadratic formula fits into u128
+const CONTRIBUTION_UNIT: u128 = 1_000_000_000_000_000_000;
+
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct FundingRound {
+    // receives whatever is left of the pot when the round is closed
+    pub admin_id: AccountId,
+    pub matching_pot: U128,
+    // donations are counted between these two moments, Unix epoch in milliseconds
+    pub starts_at: u64,
+    pub ends_at: u64,
+    // max share of the pot a single fundraiser can receive, in basis points
+    pub max_share: u32,
+    pub fundraiser_ids: Vec<FundraiserId>,
+    pub closed: bool,
+}
+
+//contributions received by one fundraiser during one round
+#[derive(BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct RoundProject {
+    // total contributed by every donor, in CONTRIBUTION_UNITs
+    pub contributions: LookupMap<AccountId, u128>,
+    pub unique_donors: u32,
+    pub total_contributed: u128,
+    // sum of the square roots of the contributions
+    pub sum_sqrt: u128,
+}
+
+impl RoundProject {
+    //the quadratic funding weight: (sum of square roots)^2 - sum of contributions
+    fn weight(&self) -> u128 {
+        (self.sum_sqrt * self.sum_sqrt).saturating_sub(self.total_contributed)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct JsonRoundProject {
+    pub fundraiser_id: FundraiserId,
+    pub unique_donors: u32,
+    // total contributed during the round, in yoctoNEAR (rounded down to micro NEAR)
+    pub total_contributed: U128,
+    // share of the pot the fundraiser would receive if the round was closed now
+    pub estimated_match: U128,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct JsonFundingRound {
+    pub round_id: FundingRoundId,
+    pub round: FundingRound,
+    pub projects: Vec<JsonRoundProject>,
+}
+
+pub(crate) fn integer_sqrt(n: u128) -> u128 {
+    if n < 2 {
+        return n;
+    }
+    //Newton's method starting from a value above the root
+    let mut x = n;
+    let mut y = x.div_ceil(2);
+    while y < x {
+        x = y;
+        y = (x + n / x) / 2;
+    }
+    x
+}
+
+/*
+    splits the pot proportionally to the weights, no share being above `max_share` basis points of the pot.
+    What capped fundraisers can't take is redistributed among the others, what nobody can take stays undistributed.
+*/
+pub(crate) fn quadratic_distribution(pot: u128, weights: &[u128], max_share: u32) -> Vec<u128> {
+    let cap = pot / u128::from(ROUND_SHARE_BASE) * u128::from(max_share)
+        + pot % u128::from(ROUND_SHARE_BASE) * u128::from(max_share) / u128::from(ROUND_SHARE_BASE);
+    let mut shares = vec![0u128; weights.len()];
+    let mut capped = vec![false; weights.len()];
+    let mut remaining_pot = pot;
+
+    loop {
+        let total_weight: u128 = weights.iter().zip(capped.iter())
+            .filter(|(_, capped)| !**capped)
+            .map(|(weight, _)| *weight)
+            .sum();
+        if total_weight == 0 || remaining_pot == 0 {
+            return shares;
+        }
+
+        let mut newly_capped = false;
+        for (index, weight) in weights.iter().enumerate() {
+            if !capped[index] && mul_div(remaining_pot, *weight, total_weight) >= cap {
+                shares[index] = cap;
+                capped[index] = true;
+                newly_capped = true;
+            }
+        }
+        if newly_capped {
+            remaining_pot = pot - shares.iter().sum::<u128>();
+            continue;
+        }
+
+        for (index, weight) in weights.iter().enumerate() {
+            if !capped[index] {
+                shares[index] = mul_div(remaining_pot, *weight, total_weight);
+            }
+        }
+        return shares;
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    //creates a round, the attached deposit minus the storage of the round becomes the matching pot
+    #[payable]
+    pub fn create_funding_round(
+        &mut self,
+        fundraiser_ids: Vec<FundraiserId>,
+        starts_at: u64,
+        ends_at: u64,
+        max_share: u32,
+    ) -> FundingRoundId {
+        self.assert_contract_owner();
+        assert!(!fundraiser_ids.is_empty(), "Abort. Round has no fundraisers");
+        assert!(fundraiser_ids.len() <= MAX_ROUND_FUNDRAISERS, "Abort. A round can have at most 50 fundraisers");
+        assert!(starts_at < ends_at, "Abort. Round must start before it ends");
+        assert!(ends_at > env::block_timestamp_ms(), "Abort. End date is in the past");
+        assert!(max_share > 0 && max_share <= ROUND_SHARE_BASE, "Abort. Max share must be between 1 and 10000 basis points");
+
+        let initial_storage_usage = env::storage_usage();
+        self.funding_round_counter += 1;
+        let round_id: FundingRoundId = self.funding_round_counter;
+
+        let mut unique_fundraiser_ids: Vec<FundraiserId> = Vec::with_capacity(fundraiser_ids.len());
+        for fundraiser_id in fundraiser_ids {
+            assert!(self.fundraisers_by_id.get(&fundraiser_id).is_some(), "Incorrect fundraiser id");
+            if unique_fundraiser_ids.contains(&fundraiser_id) {
+                continue;
+            }
+            unique_fundraiser_ids.push(fundraiser_id);
+
+            let mut rounds_set = self.funding_rounds_per_fundraiser.get(&fundraiser_id).unwrap_or_else(|| {
+                UnorderedSet::new(StorageKey::FundingRoundsPerFundraiserInner { fundraiser_id })
+            });
+            rounds_set.insert(&round_id);
+            self.funding_rounds_per_fundraiser.insert(&fundraiser_id, &rounds_set);
+        }
+
+        let mut round = FundingRound {
+            admin_id: env::predecessor_account_id(),
+            matching_pot: U128(0),
+            starts_at,
+            ends_at,
+            max_share,
+            fundraiser_ids: unique_fundraiser_ids,
+            closed: false,
+        };
+        self.funding_rounds_by_id.insert(&round_id, &round);
+
+        let storage_cost = env::storage_byte_cost().as_yoctonear() * u128::from(env::storage_usage() - initial_storage_usage);
+        let deposit = env::attached_deposit().as_yoctonear();
+        assert!(deposit > storage_cost, "Must attach more than {} yoctoNEAR to cover storage and the matching pot", storage_cost);
+        round.matching_pot = U128(deposit - storage_cost);
+        self.funding_rounds_by_id.insert(&round_id, &round);
+
+        round_id
+    }
+
+    //once the round has ended anyone can close it, which pays out the matching pot to the fundraisers
+    pub fn close_funding_round(&mut self, round_id: FundingRoundId) {
+        let mut round = self.funding_rounds_by_id.get(&round_id).expect("Incorrect funding round id");
+        assert!(!round.closed, "Funding round is already closed");
+        assert!(env::block_timestamp_ms() >= round.ends_at, "Funding round has not ended yet");
+
+        let shares = self.internal_round_distribution(round_id, &round);
+        let mut distributed: u128 = 0;
+        for (fundraiser_id, share) in round.fundraiser_ids.iter().zip(shares) {
+            if let Some(mut rounds_set) = self.funding_rounds_per_fundraiser.get(fundraiser_id) {
+                rounds_set.remove(&round_id);
+                self.funding_rounds_per_fundraiser.insert(fundraiser_id, &rounds_set);
+            }
+            //fundraisers that ended during the round still get the match for the donations they received in it
+            let fundraiser_visible = self.fundraisers_by_id.get(fundraiser_id).is_some_and(|fundraiser| fundraiser.is_visible());
+            if share > 0 && fundraiser_visible {
+                self.internal_record_donation(*fundraiser_id, &round.admin_id, &round.admin_id, share);
+                distributed += share;
+            }
+        }
+
+        round.closed = true;
+        self.funding_rounds_by_id.insert(&round_id, &round);
+
+        let leftover = round.matching_pot.0 - distributed;
+        if leftover > 0 {
+            Promise::new(round.admin_id).transfer(NearToken::from_yoctonear(leftover)).detach();
+        }
+    }
+
+    pub fn get_funding_round(&self, round_id: FundingRoundId) -> Option<JsonFundingRound> {
+        let round = self.funding_rounds_by_id.get(&round_id)?;
+        let shares = self.internal_round_distribution(round_id, &round);
+        let projects = round.fundraiser_ids.iter().zip(shares)
+            .map(|(fundraiser_id, share)| {
+                let (unique_donors, total_contributed) = self.round_projects.get(&(round_id, *fundraiser_id))
+                    .map_or((0, 0), |project| (project.unique_donors, project.total_contributed));
+                JsonRoundProject {
+                    fundraiser_id: *fundraiser_id,
+                    unique_donors,
+                    total_contributed: U128(total_contributed * CONTRIBUTION_UNIT),
+                    estimated_match: U128(share),
+                }
+            })
+            .collect();
+        Some(JsonFundingRound { round_id, round, projects })
+    }
+}
+
+impl Contract {
+    fn internal_round_distribution(&self, round_id: FundingRoundId, round: &FundingRound) -> Vec<u128> {
+        let weights: Vec<u128> = round.fundraiser_ids.iter()
+            .map(|fundraiser_id| self.round_projects.get(&(round_id, *fundraiser_id)).map_or(0, |project| project.weight()))
+            .collect();
+        quadratic_distribution(round.matching_pot.0, &weights, round.max_share)
+    }
+
+    //counts the donation in every open round the fundraiser takes part in
+    pub(crate) fn internal_record_round_contribution(&mut self, fundraiser_id: FundraiserId, donor_id: &AccountId, donation: u128, donated_at: u64) {
+        let rounds_set = match self.funding_rounds_per_fundraiser.get(&fundraiser_id) {
+            Some(rounds_set) => rounds_set,
+            None => return,
+        };
+        for round_id in rounds_set.iter() {
+            let round = self.funding_rounds_by_id.get(&round_id).unwrap();
+            //donations held by a cooling-off period count for the round they were made in
+            if round.closed || donated_at < round.starts_at || donated_at >= round.ends_at {
+                continue;
+            }
+            let mut project = self.round_projects.get(&(round_id, fundraiser_id)).unwrap_or_else(|| RoundProject {
+                contributions: LookupMap::new(StorageKey::RoundContributionsInner { round_id, fundraiser_id }),
+                unique_donors: 0,
+                total_contributed: 0,
+                sum_sqrt: 0,
+            });
+
+            let previous = project.contributions.get(donor_id).unwrap_or(0);
+            let current = previous + donation / CONTRIBUTION_UNIT;
+            if previous == 0 && current > 0 {
+                project.unique_donors += 1;
+            }
+            project.contributions.insert(donor_id, &current);
+            project.total_contributed = project.total_contributed + current - previous;
+            project.sum_sqrt = project.sum_sqrt + integer_sqrt(current) - integer_sqrt(previous);
+            self.round_projects.insert(&(round_id, fundraiser_id), &project);
+        }
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::accounts;
+    use near_sdk::testing_env;
+    use crate::test_helpers::{get_context, init_with_fundraiser, empty_token_metadata, new_fundraiser, donate, MINT_STORAGE_COST, ONE_NEAR};
+
+    fn add_fundraiser(contract: &mut Contract) -> FundraiserId {
+        testing_env!(get_context(accounts(0))
+            .storage_usage(env::storage_usage())
+            .attached_deposit(NearToken::from_yoctonear(MINT_STORAGE_COST))
+            .build());
+        contract.add_new_fundraiser(new_fundraiser("test"), empty_token_metadata());
+        contract.fundraiser_counter
+    }
+
+    #[test]
+    fn integer_sqrt_test() {
+        assert_eq!(integer_sqrt(0), 0);
+        assert_eq!(integer_sqrt(15), 3);
+        assert_eq!(integer_sqrt(16), 4);
+        assert_eq!(integer_sqrt(1_000_000_000_000), 1_000_000);
+    }
+
+    #[test]
+    fn quadratic_distribution_test() {
+        assert_eq!(quadratic_distribution(1_000, &[3, 1], ROUND_SHARE_BASE), vec![750, 250]);
+        //the first fundraiser is capped at 50%, the rest goes to the others
+        assert_eq!(quadratic_distribution(1_000, &[8, 1, 1], 5_000), vec![500, 250, 250]);
+        //nobody can take more than 30% so 10% stays undistributed
+        assert_eq!(quadratic_distribution(1_000, &[1, 1, 1], 3_000), vec![300, 300, 300]);
+        assert_eq!(quadratic_distribution(1_000, &[0, 0], ROUND_SHARE_BASE), vec![0, 0]);
+    }
+
+    #[test]
+    fn pending_donation_counts_for_round_test() {
+        let (mut contract, fundraiser_id) = init_with_fundraiser(accounts(0));
+        testing_env!(get_context(accounts(0))
+            .storage_usage(env::storage_usage())
+            .attached_deposit(NearToken::from_yoctonear(10 * ONE_NEAR))
+            .build());
+        let round_id = contract.create_funding_round(vec![fundraiser_id], 0, 1_000, ROUND_SHARE_BASE);
+        testing_env!(get_context(accounts(0))
+            .storage_usage(env::storage_usage())
+            .attached_deposit(NearToken::from_yoctonear(ONE_NEAR / 100))
+            .build());
+        contract.set_cooling_off_period(fundraiser_id, 1_000);
+
+        donate(&mut contract, accounts(1), fundraiser_id, ONE_NEAR);
+        testing_env!(get_context(accounts(2)).block_timestamp(1_500 * 1_000_000).build());
+        assert_eq!(contract.settle_donations(fundraiser_id, None), 1);
+        assert_eq!(contract.get_funding_round(round_id).unwrap().projects[0].unique_donors, 1);
+    }
+
+    #[test]
+    fn donations_on_behalf_count_once_test() {
+        let (mut contract, fundraiser_id) = init_with_fundraiser(accounts(0));
+        testing_env!(get_context(accounts(0))
+            .storage_usage(env::storage_usage())
+            .attached_deposit(NearToken::from_yoctonear(10 * ONE_NEAR))
+            .build());
+        let round_id = contract.create_funding_round(vec![fundraiser_id], 0, 1_000, ROUND_SHARE_BASE);
+
+        for donor in 2..5 {
+            testing_env!(get_context(accounts(1)).attached_deposit(NearToken::from_yoctonear(ONE_NEAR)).block_timestamp(500 * 1_000_000).build());
+            contract.donate_to_fundraiser(fundraiser_id, Some(accounts(donor)), None);
+        }
+        assert_eq!(contract.get_funding_round(round_id).unwrap().projects[0].unique_donors, 1);
+    }
+
+    #[test]
+    fn close_funding_round_test() {
+        let (mut contract, first) = init_with_fundraiser(accounts(0));
+        let second = add_fundraiser(&mut contract);
+
+        testing_env!(get_context(accounts(0))
+            .storage_usage(env::storage_usage())
+            .attached_deposit(NearToken::from_yoctonear(10 * ONE_NEAR))
+            .build());
+        let round_id = contract.create_funding_round(vec![first, second], 0, 1_000, 6_000);
+        let pot = contract.get_funding_round(round_id).unwrap().round.matching_pot.0;
+
+        //many small donors for the first fundraiser, one big donor for the second
+        for donor in 1..5 {
+            donate(&mut contract, accounts(donor), first, ONE_NEAR);
+        }
+        donate(&mut contract, accounts(5), second, 4 * ONE_NEAR);
+
+        let round = contract.get_funding_round(round_id).unwrap();
+        assert_eq!(round.projects[0].unique_donors, 4);
+        assert_eq!(round.projects[0].estimated_match, U128(pot / 10 * 6));
+        assert_eq!(round.projects[1].estimated_match, U128(0));
+
+        testing_env!(get_context(accounts(3)).block_timestamp(1_000 * 1_000_000).build());
+        contract.close_funding_round(round_id);
+        assert!(contract.get_funding_round(round_id).unwrap().round.closed);
+        assert_eq!(contract.fundraisers_by_id.get(&first).unwrap().total_collected, U128(4 * ONE_NEAR + pot / 10 * 6));
+    }
+
+    #[test]
+    #[should_panic(expected = "Funding round has not ended yet")]
+    fn close_funding_round_too_early_test() {
+        let (mut contract, fundraiser_id) = init_with_fundraiser(accounts(0));
+        testing_env!(get_context(accounts(0))
+            .storage_usage(env::storage_usage())
+            .attached_deposit(NearToken::from_yoctonear(ONE_NEAR))
+            .build());
+        let round_id = contract.create_funding_round(vec![fundraiser_id], 0, 1_000, ROUND_SHARE_BASE);
+        contract.close_funding_round(round_id);
+    }
+}
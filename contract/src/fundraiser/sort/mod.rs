@@ -0,0 +1,246 @@
+// Sort orders for listing fundraisers, each backed by an ordered index that is kept up to date on every change.
+
+use crate::*;
+
+//(sort value, fundraiser id) - the id keeps keys unique when values are equal
+pub type SortKey = (u128, FundraiserId);
+
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+#[allow(non_camel_case_types)]
+pub enum FundraiserSort {
+    NEWEST,
+    MOST_FUNDED,
+    // fundraisers with a goal that is not reached yet, smallest remaining amount first
+    CLOSEST_TO_GOAL,
+    // fundraisers with an end date that has not passed yet, earliest first
+    ENDING_SOONEST,
+}
+
+impl FundraiserSort {
+    fn descending(&self) -> bool {
+        match self {
+            FundraiserSort::NEWEST | FundraiserSort::MOST_FUNDED => true,
+            FundraiserSort::CLOSEST_TO_GOAL | FundraiserSort::ENDING_SOONEST => false,
+        }
+    }
+
+    //key the walk through the index starts after when no later cursor is given, so ended fundraisers are never read
+    fn start_after(&self) -> Option<SortKey> {
+        match self {
+            FundraiserSort::ENDING_SOONEST => Some((env::block_timestamp_ms() as u128, 0)),
+            _ => None,
+        }
+    }
+}
+
+impl Fundraiser {
+    //the key of the fundraiser in the index of the given sort, None if it doesn't belong there
+    pub(crate) fn sort_key(&self, fundraiser_id: FundraiserId, sort: &FundraiserSort) -> Option<SortKey> {
+        match sort {
+            FundraiserSort::NEWEST => Some((self.created_at as u128, fundraiser_id)),
+            FundraiserSort::MOST_FUNDED => Some((self.total_collected.0, fundraiser_id)),
+            FundraiserSort::CLOSEST_TO_GOAL => self.goal
+                .map(|goal| goal.0.saturating_sub(self.total_collected.0))
+                .filter(|remaining| *remaining > 0)
+                .map(|remaining| (remaining, fundraiser_id)),
+            FundraiserSort::ENDING_SOONEST => self.ends_at.map(|ends_at| (ends_at as u128, fundraiser_id)),
+        }
+    }
+}
+
+fn parse_sort_cursor(cursor: &str) -> SortKey {
+    let mut parts = cursor.split(':');
+    match (parts.next().map(str::parse::<u128>), parts.next().map(str::parse::<FundraiserId>), parts.next()) {
+        (Some(Ok(value)), Some(Ok(fundraiser_id)), None) => (value, fundraiser_id),
+        _ => panic!("Invalid cursor"),
+    }
+}
+
+fn sort_cursor(key: &SortKey) -> String {
+    format!("{}:{}", key.0, key.1)
+}
+
+const ALL_SORTS: [FundraiserSort; 4] = [
+    FundraiserSort::NEWEST,
+    FundraiserSort::MOST_FUNDED,
+    FundraiserSort::CLOSEST_TO_GOAL,
+    FundraiserSort::ENDING_SOONEST,
+];
+
+impl Contract {
+    fn sort_index(&self, sort: &FundraiserSort) -> &TreeMap<SortKey, ()> {
+        match sort {
+            FundraiserSort::NEWEST => &self.fundraisers_sorted_by_newest,
+            FundraiserSort::MOST_FUNDED => &self.fundraisers_sorted_by_collected,
+            FundraiserSort::CLOSEST_TO_GOAL => &self.fundraisers_sorted_by_remaining_goal,
+            FundraiserSort::ENDING_SOONEST => &self.fundraisers_sorted_by_end,
+        }
+    }
+
+    fn sort_index_mut(&mut self, sort: &FundraiserSort) -> &mut TreeMap<SortKey, ()> {
+        match sort {
+            FundraiserSort::NEWEST => &mut self.fundraisers_sorted_by_newest,
+            FundraiserSort::MOST_FUNDED => &mut self.fundraisers_sorted_by_collected,
+            FundraiserSort::CLOSEST_TO_GOAL => &mut self.fundraisers_sorted_by_remaining_goal,
+            FundraiserSort::ENDING_SOONEST => &mut self.fundraisers_sorted_by_end,
+        }
+    }
+
+    pub(crate) fn internal_add_to_sort_indices(&mut self, fundraiser_id: FundraiserId, fundraiser: &Fundraiser) {
+        for sort in ALL_SORTS.iter() {
+            if let Some(key) = fundraiser.sort_key(fundraiser_id, sort) {
+                self.sort_index_mut(sort).insert(&key, &());
+            }
+        }
+    }
+
+    pub(crate) fn internal_remove_from_sort_indices(&mut self, fundraiser_id: FundraiserId, fundraiser: &Fundraiser) {
+        for sort in ALL_SORTS.iter() {
+            if let Some(key) = fundraiser.sort_key(fundraiser_id, sort) {
+                self.sort_index_mut(sort).remove(&key);
+            }
+        }
+    }
+
+    //saves the fundraiser and moves it within the sort indices if its keys changed
+    pub(crate) fn internal_update_fundraiser(&mut self, fundraiser_id: FundraiserId, fundraiser: &Fundraiser) {
+        if let Some(previous) = self.fundraisers_by_id.insert(&fundraiser_id, fundraiser) {
+            self.internal_remove_from_sort_indices(fundraiser_id, &previous);
+        }
+        self.internal_add_to_sort_indices(fundraiser_id, fundraiser);
+    }
+
+    /*
+        walks the index of the given sort starting right after the cursor and collects the ids of
+        the fundraisers accepted by the filter. The cursor of the next page is the key of the last
+        returned fundraiser.
+    */
+    pub(crate) fn internal_sorted_fundraiser_ids<F>(
+        &self,
+        sort: &FundraiserSort,
+        cursor: Option<String>,
+        limit: Option<u64>,
+        filter: F,
+    ) -> Page<FundraiserId>
+        where F: Fn(FundraiserId, &Fundraiser) -> bool
+    {
+        let limit = page_limit(limit);
+        let index = self.sort_index(sort);
+        let cursor = match (cursor.map(|cursor| parse_sort_cursor(&cursor)), sort.start_after()) {
+            (Some(cursor), Some(start)) => Some(std::cmp::max(cursor, start)),
+            (cursor, start) => cursor.or(start),
+        };
+        let keys: Box<dyn Iterator<Item=(SortKey, ())>> = match (cursor, sort.descending()) {
+            (Some(cursor), true) => Box::new(index.iter_rev_from(cursor)),
+            (Some(cursor), false) => Box::new(index.iter_from(cursor)),
+            (None, true) => Box::new(index.iter_rev()),
+            (None, false) => Box::new(index.iter()),
+        };
+
+        let mut page: Vec<SortKey> = keys
+            .map(|(key, _)| key)
+            .filter(|key| match self.fundraisers_by_id.get(&key.1) {
+                Some(fundraiser) => filter(key.1, &fundraiser),
+                None => false,
+            })
+            .take(limit + 1)
+            .collect();
+        let next_cursor = if page.len() > limit {
+            page.pop();
+            page.last().map(sort_cursor)
+        } else {
+            None
+        };
+        Page {
+            items: page.into_iter().map(|key| key.1).collect(),
+            next_cursor,
+        }
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::accounts;
+    use near_sdk::testing_env;
+    use crate::test_helpers::{get_context, empty_token_metadata, new_fundraiser, init, donate, MINT_STORAGE_COST};
+
+    fn add_fundraiser(contract: &mut Contract, goal: Option<u128>, ends_at: Option<u64>) -> FundraiserId {
+        testing_env!(get_context(accounts(0))
+            .storage_usage(env::storage_usage())
+            .attached_deposit(NearToken::from_yoctonear(MINT_STORAGE_COST))
+            .build());
+        contract.add_new_fundraiser(
+            NewFundraiser {
+                goal: goal.map(|goal| U128(goal * MIN_DONATION)),
+                ends_at,
+                ..new_fundraiser("test")
+            },
+            empty_token_metadata(),
+        );
+        contract.fundraiser_counter
+    }
+
+    fn sorted_ids(contract: &Contract, sort: FundraiserSort) -> Vec<FundraiserId> {
+        contract.get_all_fundraisers(None, None, None, Some(sort), None, None)
+            .items
+            .into_iter()
+            .map(|json_fundraiser| json_fundraiser.fundraiser_id)
+            .collect()
+    }
+
+    #[test]
+    fn sort_orders_test() {
+        let mut contract = init(accounts(0));
+        let first = add_fundraiser(&mut contract, Some(1_000), Some(3_000_000));
+        let second = add_fundraiser(&mut contract, Some(500), None);
+        let third = add_fundraiser(&mut contract, None, Some(2_000_000));
+        donate(&mut contract, accounts(2), first, 700 * MIN_DONATION);
+        donate(&mut contract, accounts(2), second, 100 * MIN_DONATION);
+        donate(&mut contract, accounts(2), third, 900 * MIN_DONATION);
+
+        assert_eq!(sorted_ids(&contract, FundraiserSort::NEWEST), vec![third, second, first]);
+        assert_eq!(sorted_ids(&contract, FundraiserSort::MOST_FUNDED), vec![third, first, second]);
+        assert_eq!(sorted_ids(&contract, FundraiserSort::CLOSEST_TO_GOAL), vec![first, second]);
+        assert_eq!(sorted_ids(&contract, FundraiserSort::ENDING_SOONEST), vec![third, first]);
+    }
+
+    #[test]
+    fn ending_soonest_skips_ended_test() {
+        let mut contract = init(accounts(0));
+        let ended = add_fundraiser(&mut contract, None, Some(2_000_000));
+        let ending = add_fundraiser(&mut contract, None, Some(3_000_000));
+
+        testing_env!(get_context(accounts(0)).block_timestamp(2_500_000 * 1_000_000).build());
+        assert_eq!(sorted_ids(&contract, FundraiserSort::ENDING_SOONEST), vec![ending]);
+        //an old cursor doesn't bring ended fundraisers back
+        let page = contract.get_all_fundraisers(None, None, None, Some(FundraiserSort::ENDING_SOONEST), Some(format!("1:{}", ended)), None);
+        assert_eq!(page.items.iter().map(|f| f.fundraiser_id).collect::<Vec<_>>(), vec![ending]);
+    }
+
+    #[test]
+    fn sorted_cursor_test() {
+        let mut contract = init(accounts(0));
+        for amount in [300, 100, 200] {
+            let fundraiser_id = add_fundraiser(&mut contract, None, None);
+            donate(&mut contract, accounts(2), fundraiser_id, amount * MIN_DONATION);
+        }
+
+        let first_page = contract.get_all_fundraisers(None, None, None, Some(FundraiserSort::MOST_FUNDED), None, Some(2));
+        assert_eq!(first_page.items.iter().map(|f| f.fundraiser_id).collect::<Vec<_>>(), vec![1, 3]);
+        assert_eq!(first_page.next_cursor, Some(format!("{}:3", 200 * MIN_DONATION)));
+
+        let second_page = contract.get_all_fundraisers(None, None, None, Some(FundraiserSort::MOST_FUNDED), first_page.next_cursor, Some(2));
+        assert_eq!(second_page.items.iter().map(|f| f.fundraiser_id).collect::<Vec<_>>(), vec![2]);
+        assert_eq!(second_page.next_cursor, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid cursor")]
+    fn invalid_sort_cursor_test() {
+        let contract = init(accounts(0));
+        contract.get_all_fundraisers(None, None, None, Some(FundraiserSort::NEWEST), Some("abc".to_string()), None);
+    }
+}
@@ -0,0 +1,216 @@
+// Team members acting on a fundraiser on behalf of its owner, each with their own permissions.
+
+use crate::*;
+
+//max number of team members of one fundraiser
+pub const MAX_TEAM_MEMBERS: usize = 20;
+
+#[allow(non_camel_case_types)]
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub enum TeamPermission {
+    // updating the metadata of the campaign token
+    EDIT_CONTENT,
+    POST_UPDATES,
+    // paying the collected funds out to the beneficiaries
+    REQUEST_WITHDRAWALS,
+    // adding and closing reward tiers, setting the voucher key
+    MANAGE_TIERS,
+    // donation limits, visibility and allowlist, cooling-off period
+    EDIT_SETTINGS,
+    // beneficiaries and vesting schedule
+    MANAGE_PAYOUTS,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct JsonTeamMember {
+    pub account_id: AccountId,
+    pub permissions: Vec<TeamPermission>,
+}
+
+#[near_bindgen]
+impl Contract {
+    //adds a team member to the fundraiser or replaces the permissions of an existing one, the owner pays for the storage
+    #[payable]
+    pub fn set_team_member(&mut self, fundraiser_id: FundraiserId, account_id: AccountId, permissions: Vec<TeamPermission>) {
+        let fundraiser: Fundraiser = self.fundraisers_by_id.get(&fundraiser_id).expect("Incorrect fundraiser id");
+        let owner_id = env::predecessor_account_id();
+        assert_eq!(owner_id, fundraiser.owner_id, "Only the fundraiser owner can manage the team");
+        assert_ne!(account_id, fundraiser.owner_id, "Abort. The fundraiser owner can't be a team member");
+        assert!(!permissions.is_empty(), "Abort. Team members need at least one permission");
+        let mut permissions = permissions;
+        permissions.sort_by_key(|permission| *permission as u8);
+        permissions.dedup();
+
+        let initial_storage_usage = env::storage_usage();
+        let mut team = self.team_members.get(&fundraiser_id).unwrap_or_else(|| {
+            UnorderedMap::new(StorageKey::TeamMembersInner { fundraiser_id })
+        });
+        let is_new = team.insert(&account_id, &permissions).is_none();
+        assert!(team.len() as usize <= MAX_TEAM_MEMBERS, "Abort. A fundraiser can have at most 20 team members");
+        self.team_members.insert(&fundraiser_id, &team);
+        refund_deposit(env::storage_usage().saturating_sub(initial_storage_usage));
+
+        let log = TeamMemberLog { fundraiser_id, account_id, permissions, changed_by: owner_id };
+        let event = if is_new { EventLogVariant::TeamMemberAdd(vec![log]) } else { EventLogVariant::TeamMemberUpdate(vec![log]) };
+        EventLog::new(event).emit();
+    }
+
+    //the owner can remove any team member, team members can leave on their own
+    pub fn remove_team_member(&mut self, fundraiser_id: FundraiserId, account_id: AccountId) {
+        let fundraiser: Fundraiser = self.fundraisers_by_id.get(&fundraiser_id).expect("Incorrect fundraiser id");
+        let caller_id = env::predecessor_account_id();
+        assert!(
+            caller_id == fundraiser.owner_id || caller_id == account_id,
+            "Only the fundraiser owner can manage the team"
+        );
+        let mut team = self.team_members.get(&fundraiser_id).expect("Not a team member");
+        let permissions = team.remove(&account_id).expect("Not a team member");
+        if team.is_empty() {
+            self.team_members.remove(&fundraiser_id);
+        } else {
+            self.team_members.insert(&fundraiser_id, &team);
+        }
+
+        EventLog::new(EventLogVariant::TeamMemberRemove(vec![TeamMemberLog {
+            fundraiser_id,
+            account_id,
+            permissions,
+            changed_by: caller_id,
+        }])).emit();
+    }
+
+    pub fn get_team_members(&self, fundraiser_id: FundraiserId) -> Vec<JsonTeamMember> {
+        if let Some(team) = self.team_members.get(&fundraiser_id).filter(|_| self.can_view_fundraiser(fundraiser_id, None)) {
+            team.iter()
+                .map(|(account_id, permissions)| JsonTeamMember { account_id, permissions })
+                .collect()
+        } else {
+            vec![]
+        }
+    }
+}
+
+impl Contract {
+    //the owner of a fundraiser has every permission on it
+    pub(crate) fn has_fundraiser_permission(&self, fundraiser_id: FundraiserId, fundraiser: &Fundraiser, account_id: &AccountId, permission: TeamPermission) -> bool {
+        account_id == fundraiser.owner_id()
+            || self.team_members.get(&fundraiser_id)
+                .and_then(|team| team.get(account_id))
+                .is_some_and(|permissions| permissions.contains(&permission))
+    }
+
+    pub(crate) fn is_team_member(&self, fundraiser_id: FundraiserId, account_id: &AccountId) -> bool {
+        self.team_members.get(&fundraiser_id).is_some_and(|team| team.get(account_id).is_some())
+    }
+
+    //panics if the predecessor is neither the owner of the fundraiser nor a team member with the permission
+    pub(crate) fn assert_fundraiser_permission(&self, fundraiser_id: FundraiserId, fundraiser: &Fundraiser, permission: TeamPermission, action: &str) {
+        assert!(
+            self.has_fundraiser_permission(fundraiser_id, fundraiser, &env::predecessor_account_id(), permission),
+            "Only the fundraiser owner or team members with the {:?} permission can {}", permission, action
+        );
+    }
+
+    pub(crate) fn internal_clear_team(&mut self, fundraiser_id: FundraiserId) {
+        if let Some(mut team) = self.team_members.remove(&fundraiser_id) {
+            team.clear();
+        }
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::{accounts, get_logs};
+    use near_sdk::testing_env;
+    use crate::test_helpers::{get_context, init_with_fundraiser, STORAGE_DEPOSIT};
+
+    fn set_team_member(contract: &mut Contract, fundraiser_id: FundraiserId, account_id: AccountId, permissions: Vec<TeamPermission>) {
+        testing_env!(get_context(accounts(1))
+            .storage_usage(env::storage_usage())
+            .attached_deposit(NearToken::from_yoctonear(STORAGE_DEPOSIT))
+            .build());
+        contract.set_team_member(fundraiser_id, account_id, permissions);
+    }
+
+    #[test]
+    fn team_member_post_update_test() {
+        let (mut contract, fundraiser_id) = init_with_fundraiser(accounts(1));
+        set_team_member(&mut contract, fundraiser_id, accounts(2), vec![TeamPermission::POST_UPDATES]);
+        assert!(get_logs().iter().any(|log| log.contains(r#""event":"team_member_add""#)));
+        assert_eq!(contract.get_team_members(fundraiser_id), vec![JsonTeamMember {
+            account_id: accounts(2),
+            permissions: vec![TeamPermission::POST_UPDATES],
+        }]);
+
+        testing_env!(get_context(accounts(2))
+            .storage_usage(env::storage_usage())
+            .attached_deposit(NearToken::from_yoctonear(STORAGE_DEPOSIT))
+            .build());
+        contract.post_fundraiser_update(fundraiser_id, "Bought the first ambulance".to_string(), "".to_string(), None, None);
+        assert_eq!(contract.get_fundraiser_updates(fundraiser_id, None, None).items[0].author_id, accounts(2));
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the fundraiser owner or team members with the MANAGE_TIERS permission can add reward tiers")]
+    fn team_member_without_permission_test() {
+        let (mut contract, fundraiser_id) = init_with_fundraiser(accounts(1));
+        set_team_member(&mut contract, fundraiser_id, accounts(2), vec![TeamPermission::POST_UPDATES]);
+
+        testing_env!(get_context(accounts(2)).attached_deposit(NearToken::from_yoctonear(STORAGE_DEPOSIT)).build());
+        contract.add_reward_tier(fundraiser_id, NewRewardTier {
+            min_donation: U128(1),
+            unlocks_at: None,
+            title: "Sticker".to_string(),
+            description: None,
+            media: None,
+            media_hash: None,
+            max_supply: 1,
+        });
+    }
+
+    #[test]
+    fn team_member_edit_settings_test() {
+        let (mut contract, fundraiser_id) = init_with_fundraiser(accounts(1));
+        set_team_member(&mut contract, fundraiser_id, accounts(2), vec![TeamPermission::EDIT_SETTINGS]);
+
+        testing_env!(get_context(accounts(2)).build());
+        contract.set_fundraiser_visibility(fundraiser_id, FundraiserVisibility::UNLISTED);
+        assert_eq!(contract.fundraisers_by_id.get(&fundraiser_id).unwrap().visibility, FundraiserVisibility::UNLISTED);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the fundraiser owner or team members with the MANAGE_PAYOUTS permission can set the beneficiaries")]
+    fn team_member_without_payouts_permission_test() {
+        let (mut contract, fundraiser_id) = init_with_fundraiser(accounts(1));
+        set_team_member(&mut contract, fundraiser_id, accounts(2), vec![TeamPermission::EDIT_SETTINGS]);
+
+        testing_env!(get_context(accounts(2)).attached_deposit(NearToken::from_yoctonear(STORAGE_DEPOSIT)).build());
+        contract.set_beneficiaries(fundraiser_id, vec![]);
+    }
+
+    #[test]
+    fn remove_team_member_test() {
+        let (mut contract, fundraiser_id) = init_with_fundraiser(accounts(1));
+        set_team_member(&mut contract, fundraiser_id, accounts(2), vec![TeamPermission::EDIT_CONTENT]);
+
+        //team members can leave on their own
+        testing_env!(get_context(accounts(2)).build());
+        contract.remove_team_member(fundraiser_id, accounts(2));
+        assert!(contract.get_team_members(fundraiser_id).is_empty());
+        assert!(get_logs().iter().any(|log| log.contains(r#""event":"team_member_remove""#)));
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the fundraiser owner can manage the team")]
+    fn set_team_member_not_owner_test() {
+        let (mut contract, fundraiser_id) = init_with_fundraiser(accounts(1));
+        set_team_member(&mut contract, fundraiser_id, accounts(2), vec![TeamPermission::EDIT_CONTENT]);
+
+        testing_env!(get_context(accounts(2)).build());
+        contract.set_team_member(fundraiser_id, accounts(3), vec![TeamPermission::EDIT_CONTENT]);
+    }
+}
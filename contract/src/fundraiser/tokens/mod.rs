@@ -0,0 +1,100 @@
+// Keeps track of the tokens minted for a fundraiser: its campaign token, reward editions and vouchers.
+
+use crate::*;
+
+#[near_bindgen]
+impl Contract {
+    pub fn get_fundraiser_campaign_token(&self, fundraiser_id: FundraiserId) -> Option<JsonToken> {
+        if !self.can_view_fundraiser(fundraiser_id, None) {
+            return None;
+        }
+        self.campaign_token_by_fundraiser.get(&fundraiser_id).and_then(|token_id| self.nft_token(token_id))
+    }
+
+    //tokens minted for the fundraiser that weren't burned
+    pub fn get_fundraiser_tokens(&self, fundraiser_id: FundraiserId, from_index: Option<U128>, limit: Option<u64>) -> Vec<JsonToken> {
+        let tokens = match self.tokens_per_fundraiser.get(&fundraiser_id) {
+            Some(tokens) if self.can_view_fundraiser(fundraiser_id, None) => tokens,
+            _ => return vec![],
+        };
+        tokens.iter()
+            .skip(page_start(from_index))
+            .take(page_limit(limit))
+            .filter_map(|token_id| self.nft_token(token_id))
+            .collect()
+    }
+
+    pub fn get_fundraiser_of_token(&self, token_id: TokenId) -> Option<FundraiserId> {
+        self.fundraiser_by_token.get(&token_id)
+    }
+}
+
+impl Contract {
+    pub(crate) fn internal_add_token_to_fundraiser(&mut self, fundraiser_id: FundraiserId, token_id: &TokenId) {
+        let mut tokens = self.tokens_per_fundraiser.get(&fundraiser_id).unwrap_or_else(|| {
+            UnorderedSet::new(
+                StorageKey::TokensPerFundraiserInner { fundraiser_id }
+            )
+        });
+        tokens.insert(token_id);
+        self.tokens_per_fundraiser.insert(&fundraiser_id, &tokens);
+        self.fundraiser_by_token.insert(token_id, &fundraiser_id);
+    }
+
+    //forgets the fundraiser of a token that is being burned
+    pub(crate) fn internal_remove_token_from_fundraiser(&mut self, token_id: &TokenId) {
+        if let Some(fundraiser_id) = self.fundraiser_by_token.remove(token_id) {
+            let mut tokens = self.tokens_per_fundraiser.get(&fundraiser_id).unwrap();
+            tokens.remove(token_id);
+            if tokens.is_empty() {
+                self.tokens_per_fundraiser.remove(&fundraiser_id);
+            } else {
+                self.tokens_per_fundraiser.insert(&fundraiser_id, &tokens);
+            }
+            if self.campaign_token_by_fundraiser.get(&fundraiser_id).as_ref() == Some(token_id) {
+                self.campaign_token_by_fundraiser.remove(&fundraiser_id);
+            }
+        }
+    }
+
+    //forgets every token of an archived fundraiser, the tokens themselves stay with their owners
+    pub(crate) fn internal_clear_fundraiser_tokens(&mut self, fundraiser_id: FundraiserId) {
+        if let Some(mut tokens) = self.tokens_per_fundraiser.remove(&fundraiser_id) {
+            for token_id in tokens.iter() {
+                self.fundraiser_by_token.remove(&token_id);
+            }
+            tokens.clear();
+        }
+        self.campaign_token_by_fundraiser.remove(&fundraiser_id);
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use near_sdk::test_utils::accounts;
+    use near_sdk::testing_env;
+    use crate::test_helpers::{get_context, init_with_fundraiser};
+
+    #[test]
+    fn get_fundraiser_tokens_test() {
+        let (contract, fundraiser_id) = init_with_fundraiser(accounts(1));
+        let campaign_token = contract.get_fundraiser_campaign_token(fundraiser_id).unwrap();
+        assert_eq!(contract.get_fundraiser_by_id(fundraiser_id).unwrap().campaign_token, Some(campaign_token));
+        assert_eq!(contract.get_fundraiser_tokens(fundraiser_id, None, None).len(), 1);
+
+        let token_id = contract.get_fundraiser_tokens(fundraiser_id, None, None)[0].token_id.clone();
+        assert_eq!(contract.get_fundraiser_of_token(token_id), Some(fundraiser_id));
+    }
+
+    #[test]
+    fn fundraiser_without_campaign_token_test() {
+        let (mut contract, fundraiser_id) = init_with_fundraiser(accounts(1));
+        let token_id = contract.campaign_token_by_fundraiser.get(&fundraiser_id).unwrap();
+        testing_env!(get_context(accounts(1)).build());
+        contract.internal_burn(&token_id, None);
+
+        assert_eq!(contract.get_fundraiser_by_id(fundraiser_id).unwrap().campaign_token, None);
+        assert_eq!(contract.get_all_fundraisers(None, None, None, None, None, None).items.len(), 1);
+        assert!(contract.get_fundraiser_tokens(fundraiser_id, None, None).is_empty());
+    }
+}
@@ -0,0 +1,131 @@
+// Updates posted by fundraiser owners to report to their donors what happened with the money.
+
+use crate::*;
+
+const MAX_UPDATE_TITLE_LENGTH: usize = 200;
+const MAX_UPDATE_BODY_LENGTH: usize = 5000;
+
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct FundraiserUpdate {
+    // position of the update in the feed of the fundraiser, starting from 0
+    pub id: u64,
+    pub author_id: AccountId,
+    pub title: String,
+    pub body: String,
+    // URL to associated media, preferably to decentralized, content-addressed storage
+    pub media: Option<String>,
+    // Base64-encoded sha256 hash of content referenced by the `media` field
+    pub media_hash: Option<Base64VecU8>,
+    // Unix epoch in milliseconds
+    pub created_at: u64,
+}
+
+#[near_bindgen]
+impl Contract {
+    //posts an update to the feed of the fundraiser, the author pays for the storage
+    #[payable]
+    pub fn post_fundraiser_update(
+        &mut self,
+        fundraiser_id: FundraiserId,
+        title: String,
+        body: String,
+        media: Option<String>,
+        media_hash: Option<Base64VecU8>,
+    ) -> u64 {
+        let fundraiser: Fundraiser = self.fundraisers_by_id.get(&fundraiser_id).expect("Incorrect fundraiser id");
+        let author_id = env::predecessor_account_id();
+        self.assert_fundraiser_permission(fundraiser_id, &fundraiser, TeamPermission::POST_UPDATES, "post updates");
+        assert!(!title.is_empty(), "Abort. Title is empty");
+        assert!(title.len() <= MAX_UPDATE_TITLE_LENGTH, "Abort. Title is longer then 200 characters");
+        assert!(body.len() <= MAX_UPDATE_BODY_LENGTH, "Abort. Body is longer then 5000 characters");
+        assert!(media.is_some() || media_hash.is_none(), "Abort. Media hash is set without media");
+
+        let initial_storage_usage = env::storage_usage();
+
+        let mut updates = self.fundraiser_updates.get(&fundraiser_id).unwrap_or_else(|| {
+            Vector::new(StorageKey::FundraiserUpdatesInner { fundraiser_id })
+        });
+        let update_id = updates.len();
+        updates.push(&FundraiserUpdate {
+            id: update_id,
+            author_id: author_id.clone(),
+            title: title.clone(),
+            body,
+            media,
+            media_hash,
+            created_at: env::block_timestamp_ms(),
+        });
+        self.fundraiser_updates.insert(&fundraiser_id, &updates);
+
+        refund_deposit(env::storage_usage() - initial_storage_usage);
+
+        EventLog::new(EventLogVariant::FundraiserUpdate(vec![FundraiserUpdateLog {
+            fundraiser_id,
+            update_id,
+            author_id,
+            title,
+        }])).emit();
+
+        update_id
+    }
+
+    //the feed of the fundraiser, newest updates first
+    pub fn get_fundraiser_updates(&self, fundraiser_id: FundraiserId, cursor: Option<String>, limit: Option<u64>) -> Page<FundraiserUpdate> {
+        if !self.can_view_fundraiser(fundraiser_id, None) {
+            return Page { items: vec![], next_cursor: None };
+        }
+        if let Some(updates) = self.fundraiser_updates.get(&fundraiser_id) {
+            paginate_newest_first(&updates, cursor, limit, |_| true)
+        } else {
+            Page { items: vec![], next_cursor: None }
+        }
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::accounts;
+    use near_sdk::testing_env;
+    use crate::test_helpers::{get_context, init_with_fundraiser, STORAGE_DEPOSIT};
+
+    fn post_update(contract: &mut Contract, author_id: AccountId, fundraiser_id: FundraiserId, title: &str) -> u64 {
+        testing_env!(get_context(author_id)
+            .storage_usage(env::storage_usage())
+            .attached_deposit(NearToken::from_yoctonear(STORAGE_DEPOSIT))
+            .build());
+        contract.post_fundraiser_update(fundraiser_id, title.to_string(), "".to_string(), None, None)
+    }
+
+    #[test]
+    fn get_fundraiser_updates_test() {
+        let (mut contract, fundraiser_id) = init_with_fundraiser(accounts(0));
+        for title in ["first", "second", "third"] {
+            post_update(&mut contract, accounts(0), fundraiser_id, title);
+        }
+
+        let first_page = contract.get_fundraiser_updates(fundraiser_id, None, Some(2));
+        assert_eq!(first_page.items.iter().map(|update| update.title.as_str()).collect::<Vec<_>>(), vec!["third", "second"]);
+        assert_eq!(first_page.next_cursor, Some("1".to_string()));
+
+        let second_page = contract.get_fundraiser_updates(fundraiser_id, first_page.next_cursor, Some(2));
+        assert_eq!(second_page.items.iter().map(|update| update.title.as_str()).collect::<Vec<_>>(), vec!["first"]);
+        assert_eq!(second_page.next_cursor, None);
+    }
+
+    #[test]
+    fn post_fundraiser_update_emits_event_test() {
+        let (mut contract, fundraiser_id) = init_with_fundraiser(accounts(0));
+        assert_eq!(post_update(&mut contract, accounts(0), fundraiser_id, "first"), 0);
+        assert!(near_sdk::test_utils::get_logs().iter().any(|log| log.contains(r#""event":"fundraiser_update""#)));
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the fundraiser owner or team members with the POST_UPDATES permission can post updates")]
+    fn post_fundraiser_update_not_owner_test() {
+        let (mut contract, fundraiser_id) = init_with_fundraiser(accounts(0));
+        post_update(&mut contract, accounts(2), fundraiser_id, "first");
+    }
+}
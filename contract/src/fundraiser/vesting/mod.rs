@@ -0,0 +1,161 @@
+// Vesting schedules releasing the funds of a fundraiser linearly over time instead of all at once.
+
+use crate::*;
+
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct VestingSchedule {
+    // When the funds start vesting, Unix epoch in milliseconds
+    pub start_at: u64,
+    // nothing can be claimed during the first `cliff` milliseconds, what vested meanwhile is released at once after it
+    pub cliff: u64,
+    // milliseconds after which everything collected has vested
+    pub duration: u64,
+}
+
+impl VestingSchedule {
+    //part of `total` vested at `now`
+    fn vested(&self, total: u128, now: u64) -> u128 {
+        let elapsed = now.saturating_sub(self.start_at);
+        if now < self.start_at || elapsed < self.cliff {
+            0
+        } else if elapsed >= self.duration {
+            total
+        } else {
+            mul_div(total, u128::from(elapsed), u128::from(self.duration))
+        }
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /*
+        puts the funds of the fundraiser on a vesting schedule. It can only be set before the first donation
+        and can't be changed or removed afterwards, so that every donor can rely on it.
+    */
+    #[payable]
+    pub fn set_vesting_schedule(&mut self, fundraiser_id: FundraiserId, start_at: u64, cliff: u64, duration: u64) {
+        let fundraiser: Fundraiser = self.fundraisers_by_id.get(&fundraiser_id).expect("Incorrect fundraiser id");
+        self.assert_fundraiser_permission(fundraiser_id, &fundraiser, TeamPermission::MANAGE_PAYOUTS, "set the vesting schedule");
+        assert!(self.vesting_schedules.get(&fundraiser_id).is_none(), "Vesting schedule can't be changed once set");
+        assert!(
+            fundraiser.total_collected.0 == 0 && !self.has_pending_donations(fundraiser_id),
+            "Vesting schedule can only be set before the first donation"
+        );
+        assert!(start_at >= env::block_timestamp_ms(), "Abort. Vesting can't start in the past");
+        assert!(duration > 0, "Abort. Vesting duration must be greater than 0");
+        assert!(cliff <= duration, "Abort. Vesting cliff is longer than the vesting duration");
+
+        let initial_storage_usage = env::storage_usage();
+        self.vesting_schedules.insert(&fundraiser_id, &VestingSchedule { start_at, cliff, duration });
+        refund_deposit(env::storage_usage() - initial_storage_usage);
+    }
+
+    pub fn get_vesting_schedule(&self, fundraiser_id: FundraiserId) -> Option<VestingSchedule> {
+        self.vesting_schedules.get(&fundraiser_id)
+    }
+
+    //pays out what has vested so far and wasn't claimed yet, split between the beneficiaries like any other payout
+    pub fn claim_vested(&mut self, fundraiser_id: FundraiserId) -> Promise {
+        self.internal_settle_donations(fundraiser_id, MAX_SETTLEMENTS_PER_CALL);
+        let fundraiser: Fundraiser = self.fundraisers_by_id.get(&fundraiser_id).expect("Incorrect fundraiser id");
+        assert!(self.vesting_schedules.get(&fundraiser_id).is_some(), "Fundraiser has no vesting schedule");
+        assert!(
+            self.is_beneficiary(fundraiser_id, &fundraiser, &env::predecessor_account_id()),
+            "Only the beneficiaries of the fundraiser can claim vested funds"
+        );
+        self.internal_payout(fundraiser_id, &fundraiser)
+    }
+}
+
+impl Contract {
+    //part of what the fundraiser collected that can be paid out now
+    pub(crate) fn internal_vested_amount(&self, fundraiser_id: FundraiserId, total_collected: u128) -> u128 {
+        match self.vesting_schedules.get(&fundraiser_id) {
+            Some(schedule) => schedule.vested(total_collected, env::block_timestamp_ms()),
+            None => total_collected,
+        }
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::accounts;
+    use near_sdk::testing_env;
+    use crate::test_helpers::{get_context, init_with_fundraiser, ONE_NEAR, STORAGE_DEPOSIT};
+
+    fn setup() -> (Contract, FundraiserId) {
+        let (mut contract, fundraiser_id) = init_with_fundraiser(accounts(1));
+        testing_env!(get_context(accounts(1))
+            .storage_usage(env::storage_usage())
+            .attached_deposit(NearToken::from_yoctonear(STORAGE_DEPOSIT))
+            .build());
+        //vests over 10 seconds starting at 1 second, with a 2 seconds cliff
+        contract.set_vesting_schedule(fundraiser_id, 1_000, 2_000, 10_000);
+        testing_env!(get_context(accounts(2)).attached_deposit(NearToken::from_yoctonear(10 * ONE_NEAR)).build());
+        contract.donate_to_fundraiser(fundraiser_id, None, None);
+        (contract, fundraiser_id)
+    }
+
+    fn at(contract: &Contract, fundraiser_id: FundraiserId, timestamp_ms: u64) -> U128 {
+        testing_env!(get_context(accounts(1)).block_timestamp(timestamp_ms * 1_000_000).build());
+        contract.get_withdrawable_amount(fundraiser_id)
+    }
+
+    #[test]
+    fn vesting_schedule_test() {
+        let (contract, fundraiser_id) = setup();
+        assert_eq!(at(&contract, fundraiser_id, 2_999), U128(0));
+        assert_eq!(at(&contract, fundraiser_id, 3_000), U128(2 * ONE_NEAR));
+        assert_eq!(at(&contract, fundraiser_id, 6_000), U128(5 * ONE_NEAR));
+        assert_eq!(at(&contract, fundraiser_id, 20_000), U128(10 * ONE_NEAR));
+    }
+
+    #[test]
+    fn claim_vested_test() {
+        let (mut contract, fundraiser_id) = setup();
+        testing_env!(get_context(accounts(1)).block_timestamp(6_000 * 1_000_000).build());
+        contract.claim_vested(fundraiser_id).detach();
+        assert_eq!(at(&contract, fundraiser_id, 6_000), U128(0));
+        assert_eq!(at(&contract, fundraiser_id, 8_000), U128(2 * ONE_NEAR));
+    }
+
+    #[test]
+    #[should_panic(expected = "Vesting schedule can't be changed once set")]
+    fn set_vesting_schedule_twice_test() {
+        let (mut contract, fundraiser_id) = setup();
+        testing_env!(get_context(accounts(1)).attached_deposit(NearToken::from_yoctonear(STORAGE_DEPOSIT)).build());
+        contract.set_vesting_schedule(fundraiser_id, 0, 0, 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Vesting schedule can only be set before the first donation")]
+    fn set_vesting_schedule_after_donation_test() {
+        let (mut contract, fundraiser_id) = init_with_fundraiser(accounts(1));
+        testing_env!(get_context(accounts(2)).attached_deposit(NearToken::from_yoctonear(ONE_NEAR)).build());
+        contract.donate_to_fundraiser(fundraiser_id, None, None);
+        testing_env!(get_context(accounts(1)).attached_deposit(NearToken::from_yoctonear(STORAGE_DEPOSIT)).build());
+        contract.set_vesting_schedule(fundraiser_id, 0, 0, 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Abort. Vesting can't start in the past")]
+    fn set_vesting_schedule_in_past_test() {
+        let (mut contract, fundraiser_id) = init_with_fundraiser(accounts(1));
+        testing_env!(get_context(accounts(1))
+            .block_timestamp(5_000 * 1_000_000)
+            .attached_deposit(NearToken::from_yoctonear(STORAGE_DEPOSIT))
+            .build());
+        contract.set_vesting_schedule(fundraiser_id, 1_000, 0, 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the beneficiaries of the fundraiser can claim vested funds")]
+    fn claim_vested_not_beneficiary_test() {
+        let (mut contract, fundraiser_id) = setup();
+        testing_env!(get_context(accounts(3)).block_timestamp(6_000 * 1_000_000).build());
+        contract.claim_vested(fundraiser_id).detach();
+    }
+}
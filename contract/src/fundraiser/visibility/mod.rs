@@ -0,0 +1,209 @@
+// Visibility of fundraisers: public, unlisted, or restricted to an allowlist of accounts.
+
+use crate::*;
+
+#[allow(non_camel_case_types)]
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub enum FundraiserVisibility {
+    PUBLIC,
+    // left out of get_all_fundraisers, anyone knowing the id can still see it and donate
+    UNLISTED,
+    // left out of every listing, only the owner, the team and allowlisted accounts can donate and see the details
+    ALLOWLIST,
+}
+
+#[near_bindgen]
+impl Contract {
+    pub fn set_fundraiser_visibility(&mut self, fundraiser_id: FundraiserId, visibility: FundraiserVisibility) {
+        let mut fundraiser: Fundraiser = self.fundraisers_by_id.get(&fundraiser_id).expect("Incorrect fundraiser id");
+        self.assert_fundraiser_permission(fundraiser_id, &fundraiser, TeamPermission::EDIT_SETTINGS, "change its visibility");
+        fundraiser.visibility = visibility;
+        self.internal_update_fundraiser(fundraiser_id, &fundraiser);
+    }
+
+    //adds the accounts to the allowlist of the fundraiser, the caller pays for the storage
+    #[payable]
+    pub fn add_to_allowlist(&mut self, fundraiser_id: FundraiserId, account_ids: Vec<AccountId>) {
+        let fundraiser: Fundraiser = self.fundraisers_by_id.get(&fundraiser_id).expect("Incorrect fundraiser id");
+        self.assert_fundraiser_permission(fundraiser_id, &fundraiser, TeamPermission::EDIT_SETTINGS, "manage the allowlist");
+
+        let initial_storage_usage = env::storage_usage();
+        let mut allowlist = self.fundraiser_allowlists.get(&fundraiser_id).unwrap_or_else(|| {
+            UnorderedSet::new(StorageKey::FundraiserAllowlistsInner { fundraiser_id })
+        });
+        for account_id in account_ids.iter() {
+            allowlist.insert(account_id);
+        }
+        self.fundraiser_allowlists.insert(&fundraiser_id, &allowlist);
+        refund_deposit(env::storage_usage().saturating_sub(initial_storage_usage));
+    }
+
+    pub fn remove_from_allowlist(&mut self, fundraiser_id: FundraiserId, account_ids: Vec<AccountId>) {
+        let fundraiser: Fundraiser = self.fundraisers_by_id.get(&fundraiser_id).expect("Incorrect fundraiser id");
+        self.assert_fundraiser_permission(fundraiser_id, &fundraiser, TeamPermission::EDIT_SETTINGS, "manage the allowlist");
+        if let Some(mut allowlist) = self.fundraiser_allowlists.get(&fundraiser_id) {
+            for account_id in account_ids.iter() {
+                allowlist.remove(account_id);
+            }
+            if allowlist.is_empty() {
+                self.fundraiser_allowlists.remove(&fundraiser_id);
+            } else {
+                self.fundraiser_allowlists.insert(&fundraiser_id, &allowlist);
+            }
+        }
+    }
+
+    pub fn get_allowlist(&self, fundraiser_id: FundraiserId, from_index: Option<U128>, limit: Option<u64>) -> Vec<AccountId> {
+        if let Some(allowlist) = self.fundraiser_allowlists.get(&fundraiser_id) {
+            allowlist.iter()
+                .skip(page_start(from_index))
+                .take(page_limit(limit))
+                .collect()
+        } else {
+            vec![]
+        }
+    }
+
+    /*
+        details of a fundraiser as seen by the caller, allowlist-only ones included if the caller may see them.
+        It must be called with a transaction since view calls are not signed. This keeps the fundraiser out of
+        public pages, the contract state itself stays readable.
+    */
+    pub fn get_fundraiser_for_account(&self, id: FundraiserId) -> Option<JsonFundraiser> {
+        self.internal_visible_fundraiser(id, Some(&env::predecessor_account_id()))
+    }
+
+    //every fundraiser of the caller, allowlist-only ones included. Must be called with a transaction, like get_fundraiser_for_account
+    pub fn get_own_fundraisers(&self, from_index: Option<U128>, limit: Option<u64>) -> Vec<JsonFundraiser> {
+        let owner_id = env::predecessor_account_id();
+        self.internal_fundraisers_by_owner(&owner_id, from_index, limit, Some(&owner_id))
+    }
+}
+
+impl Contract {
+    //allowlist-only fundraisers are open to their owner, their team and the allowlisted accounts
+    pub(crate) fn is_allowed_on_fundraiser(&self, fundraiser_id: FundraiserId, fundraiser: &Fundraiser, account_id: &AccountId) -> bool {
+        fundraiser.visibility != FundraiserVisibility::ALLOWLIST
+            || account_id == &fundraiser.owner_id
+            || self.team_members.get(&fundraiser_id).is_some_and(|team| team.get(account_id).is_some())
+            || self.fundraiser_allowlists.get(&fundraiser_id).is_some_and(|allowlist| allowlist.contains(account_id))
+    }
+
+    /*
+        the one check deciding whether the details of a fundraiser, and everything attached to it, can be returned.
+        `viewer` is the account that signed the call, None for view calls, which only get fundraisers that are not
+        allowlist-only. Moderators can see every fundraiser.
+    */
+    pub(crate) fn can_view_fundraiser(&self, fundraiser_id: FundraiserId, viewer: Option<&AccountId>) -> bool {
+        self.fundraisers_by_id.get(&fundraiser_id).is_some_and(|fundraiser| {
+            fundraiser.visibility != FundraiserVisibility::ALLOWLIST
+                || viewer.is_some_and(|viewer| self.is_allowed_on_fundraiser(fundraiser_id, &fundraiser, viewer) || self.is_moderator(viewer))
+        })
+    }
+
+    pub(crate) fn internal_visible_fundraiser(&self, fundraiser_id: FundraiserId, viewer: Option<&AccountId>) -> Option<JsonFundraiser> {
+        if self.can_view_fundraiser(fundraiser_id, viewer) {
+            self.internal_json_fundraiser(fundraiser_id)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::accounts;
+    use near_sdk::testing_env;
+    use crate::test_helpers::{get_context, init_with_fundraiser, STORAGE_DEPOSIT};
+
+    fn setup(visibility: FundraiserVisibility) -> (Contract, FundraiserId) {
+        let (mut contract, fundraiser_id) = init_with_fundraiser(accounts(1));
+        contract.set_fundraiser_visibility(fundraiser_id, visibility);
+        testing_env!(get_context(accounts(1))
+            .storage_usage(env::storage_usage())
+            .attached_deposit(NearToken::from_yoctonear(STORAGE_DEPOSIT))
+            .build());
+        contract.add_to_allowlist(fundraiser_id, vec![accounts(2)]);
+        (contract, fundraiser_id)
+    }
+
+    #[test]
+    fn unlisted_fundraiser_test() {
+        let (contract, fundraiser_id) = setup(FundraiserVisibility::UNLISTED);
+        assert_eq!(contract.get_all_fundraisers(None, None, None, None, None, None).items.len(), 0);
+        assert!(contract.get_fundraiser_by_id(fundraiser_id).is_some());
+    }
+
+    #[test]
+    fn allowlist_fundraiser_test() {
+        let (mut contract, fundraiser_id) = setup(FundraiserVisibility::ALLOWLIST);
+        assert_eq!(contract.get_all_fundraisers(None, None, None, None, None, None).items.len(), 0);
+        assert!(contract.get_fundraiser_by_id(fundraiser_id).is_none());
+        assert!(contract.get_fundraisers_by_owner(accounts(1), None, None).is_empty());
+        testing_env!(get_context(accounts(2)).build());
+        assert!(contract.get_fundraiser_for_account(fundraiser_id).is_some());
+        testing_env!(get_context(accounts(3)).build());
+        assert!(contract.get_fundraiser_for_account(fundraiser_id).is_none());
+        testing_env!(get_context(accounts(1)).build());
+        assert_eq!(contract.get_own_fundraisers(None, None).len(), 1);
+
+        testing_env!(get_context(accounts(2)).attached_deposit(NearToken::from_yoctonear(MIN_DONATION)).build());
+        contract.donate_to_fundraiser(fundraiser_id, None, None);
+    }
+
+    #[test]
+    fn allowlist_fundraiser_sub_views_test() {
+        let (mut contract, fundraiser_id) = setup(FundraiserVisibility::ALLOWLIST);
+        testing_env!(get_context(accounts(2))
+            .storage_usage(env::storage_usage())
+            .attached_deposit(NearToken::from_yoctonear(STORAGE_DEPOSIT))
+            .build());
+        contract.post_comment(fundraiser_id, "Slava Ukraini".to_string());
+
+        assert!(contract.get_fundraiser_comments(fundraiser_id, None, None).items.is_empty());
+        assert!(contract.get_fundraiser_campaign_token(fundraiser_id).is_none());
+        assert!(contract.get_fundraiser_tokens(fundraiser_id, None, None).is_empty());
+        assert!(contract.get_beneficiaries(fundraiser_id).is_empty());
+
+        testing_env!(get_context(accounts(1)).build());
+        contract.set_fundraiser_visibility(fundraiser_id, FundraiserVisibility::UNLISTED);
+        assert_eq!(contract.get_fundraiser_comments(fundraiser_id, None, None).items.len(), 1);
+        assert!(contract.get_fundraiser_campaign_token(fundraiser_id).is_some());
+        assert_eq!(contract.get_beneficiaries(fundraiser_id).len(), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only allowlisted accounts can comment on this fundraiser")]
+    fn allowlist_fundraiser_comment_test() {
+        let (mut contract, fundraiser_id) = setup(FundraiserVisibility::ALLOWLIST);
+        testing_env!(get_context(accounts(3)).attached_deposit(NearToken::from_yoctonear(STORAGE_DEPOSIT)).build());
+        contract.post_comment(fundraiser_id, "Slava Ukraini".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Only allowlisted accounts can report this fundraiser")]
+    fn allowlist_fundraiser_report_test() {
+        let (mut contract, fundraiser_id) = setup(FundraiserVisibility::ALLOWLIST);
+        testing_env!(get_context(accounts(3)).attached_deposit(NearToken::from_yoctonear(STORAGE_DEPOSIT)).build());
+        contract.report_fundraiser(fundraiser_id, ReportReason::SPAM, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only allowlisted accounts can donate to this fundraiser")]
+    fn allowlist_fundraiser_donation_on_behalf_test() {
+        let (mut contract, fundraiser_id) = setup(FundraiserVisibility::ALLOWLIST);
+        testing_env!(get_context(accounts(3)).attached_deposit(NearToken::from_yoctonear(MIN_DONATION)).build());
+        contract.donate_to_fundraiser(fundraiser_id, Some(accounts(2)), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only allowlisted accounts can donate to this fundraiser")]
+    fn allowlist_fundraiser_donation_test() {
+        let (mut contract, fundraiser_id) = setup(FundraiserVisibility::ALLOWLIST);
+        testing_env!(get_context(accounts(3)).attached_deposit(NearToken::from_yoctonear(MIN_DONATION)).build());
+        contract.donate_to_fundraiser(fundraiser_id, None, None);
+    }
+}
@@ -0,0 +1,169 @@
+// Lazy minting: vouchers signed off-chain by the fundraiser owner and redeemed for a token on demand.
+
+use std::convert::TryFrom;
+use near_sdk::{CurveType, PublicKey};
+use crate::*;
+
+/*
+    what the fundraiser owner signs. The signed message is the borsh serialization of the voucher,
+    every voucher of a fundraiser must have its own nonce. The contract account id keeps a voucher
+    signed for one deployment from being claimed on another one.
+*/
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Debug, PartialEq)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct Voucher {
+    pub contract_id: AccountId,
+    pub fundraiser_id: FundraiserId,
+    pub nonce: u64,
+    // the only account allowed to claim it, anyone can if not set
+    pub recipient_id: Option<AccountId>,
+    // When the voucher stops being claimable, Unix epoch in milliseconds
+    pub expires_at: u64,
+    pub metadata: TokenMetadata,
+}
+
+//checks an ed25519 signature, the key is stored with its curve type as first byte
+fn verify_ed25519(signature: &[u8], message: &[u8], public_key: &PublicKey) -> bool {
+    match (<&[u8; 64]>::try_from(signature), <&[u8; 32]>::try_from(&public_key.as_bytes()[1..])) {
+        (Ok(signature), Ok(public_key)) => env::ed25519_verify(signature, message, public_key),
+        _ => false,
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    //sets the key the vouchers of the fundraiser are signed with, replacing the previous one
+    #[payable]
+    pub fn set_voucher_key(&mut self, fundraiser_id: FundraiserId, public_key: PublicKey) {
+        let fundraiser: Fundraiser = self.fundraisers_by_id.get(&fundraiser_id).expect("Incorrect fundraiser id");
+        self.assert_fundraiser_permission(fundraiser_id, &fundraiser, TeamPermission::MANAGE_TIERS, "set the voucher key");
+        assert!(public_key.curve_type() == CurveType::ED25519, "Abort. Voucher key must be an ed25519 key");
+
+        let initial_storage_usage = env::storage_usage();
+        self.voucher_keys.insert(&fundraiser_id, &public_key);
+        refund_deposit(env::storage_usage().saturating_sub(initial_storage_usage));
+    }
+
+    pub fn get_voucher_key(&self, fundraiser_id: FundraiserId) -> Option<PublicKey> {
+        self.voucher_keys.get(&fundraiser_id)
+    }
+
+    pub fn is_voucher_claimed(&self, fundraiser_id: FundraiserId, nonce: u64) -> bool {
+        self.claimed_vouchers.contains(&(fundraiser_id, nonce))
+    }
+
+    //mints the token of the voucher to the caller, who pays for the storage
+    #[payable]
+    pub fn claim_voucher(&mut self, voucher: Voucher, signature: Base64VecU8) -> TokenId {
+        assert_eq!(voucher.contract_id, env::current_account_id(), "Voucher was issued for another contract");
+        let public_key = self.voucher_keys.get(&voucher.fundraiser_id).expect("Fundraiser has no voucher key");
+        assert!(
+            verify_ed25519(&signature.0, &borsh::to_vec(&voucher).unwrap(), &public_key),
+            "Invalid voucher signature"
+        );
+        assert!(env::block_timestamp_ms() < voucher.expires_at, "Voucher has expired");
+        let claimant_id = env::predecessor_account_id();
+        if let Some(recipient_id) = &voucher.recipient_id {
+            assert_eq!(&claimant_id, recipient_id, "Voucher was issued to another account");
+        }
+
+        let initial_storage_usage = env::storage_usage();
+        assert!(self.claimed_vouchers.insert(&(voucher.fundraiser_id, voucher.nonce)), "Voucher was already claimed");
+        let token_id = self.internal_next_token_id("voucher");
+        self.internal_mint(token_id.clone(), claimant_id, voucher.metadata);
+        self.internal_add_token_to_fundraiser(voucher.fundraiser_id, &token_id);
+        refund_deposit(env::storage_usage() - initial_storage_usage);
+
+        token_id
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use ed25519_dalek::{Keypair, SecretKey, Signer};
+    use near_sdk::test_utils::accounts;
+    use near_sdk::testing_env;
+    use crate::test_helpers::{get_context, init_with_fundraiser, empty_token_metadata, MINT_STORAGE_COST, STORAGE_DEPOSIT};
+
+    fn keypair() -> Keypair {
+        let secret = SecretKey::from_bytes(&[7; 32]).unwrap();
+        let public = (&secret).into();
+        Keypair { secret, public }
+    }
+
+    fn setup() -> (Contract, FundraiserId) {
+        let (mut contract, fundraiser_id) = init_with_fundraiser(accounts(0));
+        let mut public_key = vec![CurveType::ED25519 as u8];
+        public_key.extend_from_slice(keypair().public.as_bytes());
+        testing_env!(get_context(accounts(0))
+            .storage_usage(env::storage_usage())
+            .attached_deposit(NearToken::from_yoctonear(MINT_STORAGE_COST))
+            .build());
+        contract.set_voucher_key(fundraiser_id, PublicKey::try_from(public_key).unwrap());
+        (contract, fundraiser_id)
+    }
+
+    fn voucher(fundraiser_id: FundraiserId, recipient_id: Option<AccountId>) -> Voucher {
+        Voucher { contract_id: accounts(0), fundraiser_id, nonce: 1, recipient_id, expires_at: 1_000, metadata: empty_token_metadata() }
+    }
+
+    fn claim(contract: &mut Contract, claimant_id: AccountId, voucher: Voucher, signature: Vec<u8>) -> TokenId {
+        testing_env!(get_context(claimant_id)
+            .storage_usage(env::storage_usage())
+            .attached_deposit(NearToken::from_yoctonear(STORAGE_DEPOSIT))
+            .build());
+        contract.claim_voucher(voucher, Base64VecU8(signature))
+    }
+
+    fn sign(voucher: &Voucher) -> Vec<u8> {
+        keypair().sign(&borsh::to_vec(voucher).unwrap()).to_bytes().to_vec()
+    }
+
+    #[test]
+    fn claim_voucher_test() {
+        let (mut contract, fundraiser_id) = setup();
+        let signature = sign(&voucher(fundraiser_id, None));
+
+        let token_id = claim(&mut contract, accounts(2), voucher(fundraiser_id, None), signature);
+        assert_eq!(contract.nft_token(token_id.clone()).unwrap().owner_id, accounts(2));
+        assert_eq!(contract.get_fundraiser_of_token(token_id), Some(fundraiser_id));
+        assert!(contract.is_voucher_claimed(fundraiser_id, 1));
+    }
+
+    #[test]
+    #[should_panic(expected = "Voucher was already claimed")]
+    fn claim_voucher_replay_test() {
+        let (mut contract, fundraiser_id) = setup();
+        let signature = sign(&voucher(fundraiser_id, None));
+        claim(&mut contract, accounts(2), voucher(fundraiser_id, None), signature.clone());
+        claim(&mut contract, accounts(3), voucher(fundraiser_id, None), signature);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid voucher signature")]
+    fn claim_voucher_tampered_test() {
+        let (mut contract, fundraiser_id) = setup();
+        let signature = sign(&voucher(fundraiser_id, Some(accounts(2))));
+        //the recipient was removed from the signed voucher
+        claim(&mut contract, accounts(3), voucher(fundraiser_id, None), signature);
+    }
+
+    #[test]
+    #[should_panic(expected = "Voucher was issued for another contract")]
+    fn claim_voucher_other_contract_test() {
+        let (mut contract, fundraiser_id) = setup();
+        let voucher = Voucher { contract_id: accounts(4), ..voucher(fundraiser_id, None) };
+        let signature = sign(&voucher);
+        claim(&mut contract, accounts(2), voucher, signature);
+    }
+
+    #[test]
+    #[should_panic(expected = "Voucher was issued to another account")]
+    fn claim_voucher_other_recipient_test() {
+        let (mut contract, fundraiser_id) = setup();
+        let signature = sign(&voucher(fundraiser_id, Some(accounts(2))));
+        claim(&mut contract, accounts(3), voucher(fundraiser_id, Some(accounts(2))), signature);
+    }
+}
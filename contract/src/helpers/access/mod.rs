@@ -0,0 +1,17 @@
+use crate::*;
+
+impl Contract {
+    //panics if the predecessor is not the contract owner
+    pub(crate) fn assert_contract_owner(&self) {
+        assert_eq!(env::predecessor_account_id(), self.owner_id, "Only the contract owner can call this method");
+    }
+
+    //the contract owner is always treated as a moderator
+    pub(crate) fn is_moderator(&self, account_id: &AccountId) -> bool {
+        account_id == &self.owner_id || self.moderators.contains(account_id)
+    }
+
+    pub(crate) fn assert_moderator(&self) {
+        assert!(self.is_moderator(&env::predecessor_account_id()), "Only moderators can call this method");
+    }
+}
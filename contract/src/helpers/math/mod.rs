@@ -0,0 +1,43 @@
+// Arithmetic shared by the modules that split amounts by ratios.
+
+//a * b / c rounded down, computed on 256 bits so it doesn't overflow as long as the result fits into u128
+pub fn mul_div(a: u128, b: u128, c: u128) -> u128 {
+    const LOW: u128 = u64::MAX as u128;
+    //a * b split into a high and a low u128
+    let (a_high, a_low) = (a >> 64, a & LOW);
+    let (b_high, b_low) = (b >> 64, b & LOW);
+    let (low_low, low_high, high_low) = (a_low * b_low, a_low * b_high, a_high * b_low);
+    let middle = (low_low >> 64) + (low_high & LOW) + (high_low & LOW);
+    let low = (middle << 64) | (low_low & LOW);
+    let high = a_high * b_high + (low_high >> 64) + (high_low >> 64) + (middle >> 64);
+
+    //long division, one bit at a time
+    let mut quotient: u128 = 0;
+    let mut remainder: u128 = 0;
+    for i in (0..256).rev() {
+        let bit = if i >= 128 { (high >> (i - 128)) & 1 } else { (low >> i) & 1 };
+        let overflow = remainder >> 127 == 1;
+        remainder = (remainder << 1) | bit;
+        if overflow || remainder >= c {
+            remainder = remainder.wrapping_sub(c);
+            if i < 128 {
+                quotient |= 1 << i;
+            }
+        }
+    }
+    quotient
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use crate::test_helpers::ONE_NEAR;
+
+    #[test]
+    fn mul_div_test() {
+        assert_eq!(mul_div(10, 3_333, 10_000), 3);
+        assert_eq!(mul_div(u128::MAX, 2, 4), u128::MAX / 2);
+        //the divisor is too big for c * c to fit into u128
+        assert_eq!(mul_div(1_000 * ONE_NEAR, 1_500 * ONE_NEAR, 3_000 * ONE_NEAR), 500 * ONE_NEAR);
+    }
+}
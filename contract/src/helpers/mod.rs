@@ -1,2 +1,5 @@
 pub use pagination::*;
+pub use math::*;
 mod pagination;
+mod math;
+mod access;
@@ -1,10 +1,105 @@
-pub fn pagination(page: Option<u32>) -> (u32, u32) {
-    let per_page: u32 = 25;
-    match page {
-        Some(page) => {
-            let from_index = (page * per_page) - per_page;
-            (from_index, per_page)
-        }
-        None => (0, per_page)
-    }
-}
\ No newline at end of file
+// Pagination shared by all the list views of the contract.
+
+use crate::*;
+
+//number of items returned when the caller doesn't specify a limit
+pub const DEFAULT_PAGE_LIMIT: u64 = 25;
+//hard cap on the number of items returned by a single view call
+pub const MAX_PAGE_LIMIT: u64 = 100;
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    //pass it back as `cursor` to get the next page. None when there is nothing left
+    pub next_cursor: Option<String>,
+}
+
+//returns the number of items to take, never more than MAX_PAGE_LIMIT
+pub fn page_limit(limit: Option<u64>) -> usize {
+    let limit = limit.unwrap_or(DEFAULT_PAGE_LIMIT);
+    assert!(limit > 0, "Limit must be greater than 0");
+    std::cmp::min(limit, MAX_PAGE_LIMIT) as usize
+}
+
+//where to start index based pagination - if we have a from_index, we'll use that - otherwise start from 0 index
+pub fn page_start(from_index: Option<U128>) -> usize {
+    u128::from(from_index.unwrap_or(U128(0))) as usize
+}
+
+//index based pagination, the cursor is the position of the first item of the page
+pub fn paginate<T>(items: impl Iterator<Item=T>, cursor: Option<String>, limit: Option<u64>) -> Page<T> {
+    let start: usize = match cursor {
+        Some(cursor) => cursor.parse().expect("Invalid cursor"),
+        None => 0,
+    };
+    let limit = page_limit(limit);
+    //take one item more than requested to find out if there is a next page
+    let mut items: Vec<T> = items.skip(start).take(limit + 1).collect();
+    let next_cursor = if items.len() > limit {
+        items.pop();
+        Some((start + limit).to_string())
+    } else {
+        None
+    };
+    Page { items, next_cursor }
+}
+
+/*
+    pagination over an append-only vector, newest items first. The cursor is the position right after
+    the next item to return, so it stays valid when new items are appended between calls.
+    Items rejected by the filter are skipped.
+*/
+pub fn paginate_newest_first<T, F>(items: &Vector<T>, cursor: Option<String>, limit: Option<u64>, filter: F) -> Page<T>
+    where T: BorshSerialize + BorshDeserialize,
+          F: Fn(&T) -> bool
+{
+    let end: u64 = match cursor {
+        Some(cursor) => std::cmp::min(cursor.parse().expect("Invalid cursor"), items.len()),
+        None => items.len(),
+    };
+    let limit = page_limit(limit);
+    let mut page: Vec<(u64, T)> = (0..end).rev()
+        .filter_map(|index| items.get(index).map(|item| (index, item)))
+        .filter(|(_, item)| filter(item))
+        .take(limit + 1)
+        .collect();
+    let next_cursor = if page.len() > limit {
+        page.pop();
+        page.last().map(|(index, _)| index.to_string())
+    } else {
+        None
+    };
+    Page {
+        items: page.into_iter().map(|(_, item)| item).collect(),
+        next_cursor,
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paginate_test() {
+        let first = paginate(0..5, None, Some(2));
+        assert_eq!(first.items, vec![0, 1]);
+        assert_eq!(first.next_cursor, Some("2".to_string()));
+
+        let last = paginate(0..5, Some("4".to_string()), Some(2));
+        assert_eq!(last.items, vec![4]);
+        assert_eq!(last.next_cursor, None);
+    }
+
+    #[test]
+    fn page_limit_test() {
+        assert_eq!(page_limit(None), DEFAULT_PAGE_LIMIT as usize);
+        assert_eq!(page_limit(Some(1_000)), MAX_PAGE_LIMIT as usize);
+    }
+
+    #[test]
+    #[should_panic(expected = "Limit must be greater than 0")]
+    fn page_limit_zero_test() {
+        page_limit(Some(0));
+    }
+}
@@ -2,8 +2,8 @@ extern crate core;
 
 use std::collections::HashMap;
 use std::fmt::Debug;
-use near_sdk::{PromiseOrValue, Promise, near_bindgen, PanicOnDefault, BorshStorageKey, AccountId, borsh::{self, BorshDeserialize, BorshSerialize}, serde::{Deserialize, Serialize}, env, CryptoHash, log};
-use near_sdk::collections::{LazyOption, LookupMap, UnorderedMap, UnorderedSet, Vector};
+use near_sdk::{PromiseOrValue, Promise, NearToken, near_bindgen, PanicOnDefault, BorshStorageKey, AccountId, borsh::{self, BorshDeserialize, BorshSerialize}, serde::{Deserialize, Serialize}, env, CryptoHash, log};
+use near_sdk::collections::{LazyOption, LookupMap, LookupSet, TreeMap, UnorderedMap, UnorderedSet, Vector};
 use near_sdk::json_types::{U128, Base64VecU8};
 
 use crate::fundraiser::*;
@@ -11,6 +11,7 @@ use crate::nft::*;
 use crate::helpers::*;
 use crate::test::*;
 use crate::seeds::*;
+use crate::events::*;
 
 mod nft;
 mod fundraiser;
@@ -18,9 +19,11 @@ mod helpers;
 mod test;
 mod seeds;
 mod config;
+mod events;
 
 #[near_bindgen]
 #[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
+#[borsh(crate = "near_sdk::borsh")]
 pub struct Contract {
     // token: NonFungibleToken,
     // metadata: LazyOption<NFTContractMetadata>,
@@ -36,6 +39,84 @@ pub struct Contract {
     pub fundraisers_donations: UnorderedMap<FundraiserId, UnorderedMap<AccountId, Vector<u128>>>, // fundraiser_id => (who donated => [amounts])
     pub fundraiser_counter: u32,
 
+    //accounts allowed to review reports and hide or take down fundraisers
+    pub moderators: UnorderedSet<AccountId>,
+    //keeps track of the reports filed against a given fundraiser
+    pub fundraiser_reports: LookupMap<FundraiserId, Vector<Report>>,
+    //fundraisers with reports waiting for a moderator
+    pub reported_fundraisers: UnorderedSet<FundraiserId>,
+
+    //secondary indices used for filtered listing of fundraisers
+    pub fundraisers_by_category: LookupMap<FundraiserCategory, UnorderedSet<FundraiserId>>,
+    pub fundraisers_by_tag: LookupMap<String, UnorderedSet<FundraiserId>>,
+    pub fundraisers_by_status: LookupMap<FundraiserStatus, UnorderedSet<FundraiserId>>,
+
+    //ordered indices backing the sort options of get_all_fundraisers
+    pub fundraisers_sorted_by_newest: TreeMap<SortKey, ()>,
+    pub fundraisers_sorted_by_collected: TreeMap<SortKey, ()>,
+    pub fundraisers_sorted_by_remaining_goal: TreeMap<SortKey, ()>,
+    pub fundraisers_sorted_by_end: TreeMap<SortKey, ()>,
+
+    //keeps track of the updates posted by the owner of a given fundraiser
+    pub fundraiser_updates: LookupMap<FundraiserId, Vector<FundraiserUpdate>>,
+    //keeps track of the comments left on a given fundraiser
+    pub fundraiser_comments: LookupMap<FundraiserId, Vector<FundraiserComment>>,
+
+    pub pledges_by_id: LookupMap<PledgeId, Pledge>,
+    //active pledges ordered by the time their next payment is due
+    pub pledges_by_due_date: TreeMap<(u64, PledgeId), ()>,
+    pub pledges_per_donor: LookupMap<AccountId, UnorderedSet<PledgeId>>,
+    pub pledge_counter: PledgeId,
+
+    pub matching_pools_by_id: LookupMap<MatchingPoolId, MatchingPool>,
+    //keeps track of the pools matching donations to a given fundraiser or category
+    pub matching_pools_per_target: LookupMap<MatchingTarget, UnorderedSet<MatchingPoolId>>,
+    pub matching_pool_counter: MatchingPoolId,
+
+    pub funding_rounds_by_id: LookupMap<FundingRoundId, FundingRound>,
+    //keeps track of the rounds a fundraiser takes part in
+    pub funding_rounds_per_fundraiser: LookupMap<FundraiserId, UnorderedSet<FundingRoundId>>,
+    //contributions received by a fundraiser during a round
+    pub round_projects: LookupMap<(FundingRoundId, FundraiserId), RoundProject>,
+    pub funding_round_counter: FundingRoundId,
+
+    //keeps track of the reward tiers of a given fundraiser
+    pub reward_tiers: LookupMap<FundraiserId, Vector<RewardTier>>,
+    //the donor who gave the most to a given fundraiser and how much
+    pub top_donor_by_fundraiser: LookupMap<FundraiserId, (AccountId, u128)>,
+    //number of fundraisers a given account has donated to
+    pub campaigns_supported_per_donor: LookupMap<AccountId, u32>,
+    //key the vouchers of a given fundraiser are signed with
+    pub voucher_keys: LookupMap<FundraiserId, near_sdk::PublicKey>,
+    //nonces of the vouchers that were already claimed, per fundraiser
+    pub claimed_vouchers: LookupSet<(FundraiserId, u64)>,
+    //campaign token minted when a given fundraiser was created
+    pub campaign_token_by_fundraiser: LookupMap<FundraiserId, TokenId>,
+    //keeps track of all the token IDs minted for a given fundraiser: its campaign token, reward editions and vouchers
+    pub tokens_per_fundraiser: LookupMap<FundraiserId, UnorderedSet<TokenId>>,
+    //the fundraiser a given token was minted for
+    pub fundraiser_by_token: LookupMap<TokenId, FundraiserId>,
+    //accounts the collected funds of a given fundraiser are split between, its owner when not set
+    pub beneficiaries_by_fundraiser: LookupMap<FundraiserId, Vec<Beneficiary>>,
+    //how much of the funds collected by a given fundraiser was already paid out
+    pub withdrawn_by_fundraiser: LookupMap<FundraiserId, u128>,
+    //accounts acting on a given fundraiser on behalf of its owner, and what they are allowed to do
+    pub team_members: LookupMap<FundraiserId, UnorderedMap<AccountId, Vec<TeamPermission>>>,
+    //schedule the funds collected by a given fundraiser are released on, everything can be paid out when not set
+    pub vesting_schedules: LookupMap<FundraiserId, VestingSchedule>,
+    //min donation, max per donor and hard cap of a given fundraiser
+    pub donation_limits: LookupMap<FundraiserId, DonationLimits>,
+    //accounts allowed to donate to a given allowlist-only fundraiser
+    pub fundraiser_allowlists: LookupMap<FundraiserId, UnorderedSet<AccountId>>,
+    //cooling-off period of a given fundraiser, in milliseconds
+    pub cooling_off_periods: LookupMap<FundraiserId, u64>,
+    //donations still in their cooling-off period, by fundraiser and position in its queue
+    pub pending_donations: LookupMap<(FundraiserId, u64), PendingDonation>,
+    //(position of the first donation that isn't settled yet, position of the next donation) in the queue of a given fundraiser
+    pub pending_donations_range: LookupMap<FundraiserId, (u64, u64)>,
+    //total a given account paid to a given fundraiser, for itself or on behalf of others
+    pub donations_paid_by: LookupMap<(FundraiserId, AccountId), u128>,
+
 
 
     //keeps track of all the token IDs for a given account
@@ -44,11 +125,23 @@ pub struct Contract {
     pub tokens_by_id: LookupMap<TokenId, Token>,
     //keeps track of the token metadata for a given token ID
     pub token_metadata_by_id: UnorderedMap<TokenId, TokenMetadata>,
+    //keeps track of the series and edition of tokens minted from a series, they have no metadata of their own
+    pub token_editions_by_id: UnorderedMap<TokenId, TokenEdition>,
+    pub token_series_by_id: LookupMap<SeriesId, TokenSeries>,
+    pub series_counter: SeriesId,
+    //number of tokens the contract minted on its own, used to build their ids
+    pub contract_token_counter: u64,
+    //keeps track of the soulbound badges, nft_tokens lists them but owners enumerate them with nft_badges_for_owner
+    pub badges_by_id: UnorderedMap<TokenId, Badge>,
+    pub badges_per_owner: LookupMap<AccountId, UnorderedSet<TokenId>>,
+    //keeps track of the previous metadata of a given token
+    pub token_metadata_revisions: LookupMap<TokenId, Vector<TokenMetadataRevision>>,
 
 }
 
 /// Helper structure for keys of the persistent collections.
-#[derive(BorshSerialize)]
+#[derive(BorshSerialize, BorshStorageKey)]
+#[borsh(crate = "near_sdk::borsh")]
 pub enum StorageKey {
     TokensPerOwner,
     TokenPerOwnerInner { account_id_hash: CryptoHash },
@@ -58,6 +151,65 @@ pub enum StorageKey {
     TokensPerType,
     TokensPerTypeInner { token_type_hash: CryptoHash },
     TokenTypesLocked,
+    Moderators,
+    FundraiserReports,
+    FundraiserReportsInner { fundraiser_id: FundraiserId },
+    ReportedFundraisers,
+    FundraisersByCategory,
+    FundraisersByCategoryInner { category: FundraiserCategory },
+    FundraisersByTag,
+    FundraisersByTagInner { tag_hash: CryptoHash },
+    FundraisersByStatus,
+    FundraisersByStatusInner { status: FundraiserStatus },
+    FundraisersSortedByNewest,
+    FundraisersSortedByCollected,
+    FundraisersSortedByRemainingGoal,
+    FundraisersSortedByEnd,
+    FundraiserUpdates,
+    FundraiserUpdatesInner { fundraiser_id: FundraiserId },
+    FundraiserComments,
+    FundraiserCommentsInner { fundraiser_id: FundraiserId },
+    PledgesById,
+    PledgesByDueDate,
+    PledgesPerDonor,
+    PledgesPerDonorInner { account_id_hash: CryptoHash },
+    MatchingPoolsById,
+    MatchingPoolsPerTarget,
+    MatchingPoolsPerTargetInner { target_hash: CryptoHash },
+    FundingRoundsById,
+    FundingRoundsPerFundraiser,
+    FundingRoundsPerFundraiserInner { fundraiser_id: FundraiserId },
+    RoundProjects,
+    RoundContributionsInner { round_id: FundingRoundId, fundraiser_id: FundraiserId },
+    RewardTiers,
+    RewardTiersInner { fundraiser_id: FundraiserId },
+    TokenEditionsById,
+    TokenSeriesById,
+    TopDonorByFundraiser,
+    CampaignsSupportedPerDonor,
+    BadgesById,
+    BadgesPerOwner,
+    BadgesPerOwnerInner { account_id_hash: CryptoHash },
+    VoucherKeys,
+    ClaimedVouchers,
+    TokenMetadataRevisions,
+    TokenMetadataRevisionsInner { token_id_hash: CryptoHash },
+    CampaignTokenByFundraiser,
+    TokensPerFundraiser,
+    TokensPerFundraiserInner { fundraiser_id: FundraiserId },
+    FundraiserByToken,
+    BeneficiariesByFundraiser,
+    WithdrawnByFundraiser,
+    TeamMembers,
+    TeamMembersInner { fundraiser_id: FundraiserId },
+    VestingSchedules,
+    DonationLimits,
+    FundraiserAllowlists,
+    FundraiserAllowlistsInner { fundraiser_id: FundraiserId },
+    CoolingOffPeriods,
+    PendingDonations,
+    PendingDonationsRange,
+    DonationsPaidBy,
 }
 
 #[near_bindgen]
@@ -71,8 +223,8 @@ impl Contract {
     pub fn new_default_meta(owner_id: AccountId) -> Self {
         Self::new(owner_id, NFTContractMetadata {
             spec: "u_fundraisers_1.0.0".to_string(),
-            name: "u_fundraisers".to_string(),
-            symbol: "Ukraine Fundraisers".to_string(),
+            name: "Ukraine Fundraisers".to_string(),
+            symbol: "u_fundraisers".to_string(),
             icon: None,
             base_uri: None,
             reference: None,
@@ -87,18 +239,73 @@ impl Contract {
     */
     #[init]
     pub fn new(owner_id: AccountId, metadata: NFTContractMetadata) -> Self {
+        metadata.assert_valid();
         let this = Self {
             owner_id,
 
-            tokens_per_owner: LookupMap::new(StorageKey::TokensPerOwner.try_to_vec().unwrap()),
-            tokens_by_id: LookupMap::new(StorageKey::TokensById.try_to_vec().unwrap()),
-            token_metadata_by_id: UnorderedMap::new(StorageKey::TokenMetadataById.try_to_vec().unwrap()),
-            metadata: LazyOption::new(StorageKey::NFTContractMetadata.try_to_vec().unwrap(), Some(&metadata)),
+            tokens_per_owner: LookupMap::new(StorageKey::TokensPerOwner),
+            tokens_by_id: LookupMap::new(StorageKey::TokensById),
+            token_metadata_by_id: UnorderedMap::new(StorageKey::TokenMetadataById),
+            token_editions_by_id: UnorderedMap::new(StorageKey::TokenEditionsById),
+            token_series_by_id: LookupMap::new(StorageKey::TokenSeriesById),
+            series_counter: 0,
+            contract_token_counter: 0,
+            badges_by_id: UnorderedMap::new(StorageKey::BadgesById),
+            badges_per_owner: LookupMap::new(StorageKey::BadgesPerOwner),
+            token_metadata_revisions: LookupMap::new(StorageKey::TokenMetadataRevisions),
+            metadata: LazyOption::new(StorageKey::NFTContractMetadata, Some(&metadata)),
 
             fundraiser_per_owner: LookupMap::new(b"m"),
             fundraisers_by_id: UnorderedMap::new(b"v"),
             fundraiser_counter: 0,
             fundraisers_donations: UnorderedMap::new(b"d"),
+
+            moderators: UnorderedSet::new(StorageKey::Moderators),
+            fundraiser_reports: LookupMap::new(StorageKey::FundraiserReports),
+            reported_fundraisers: UnorderedSet::new(StorageKey::ReportedFundraisers),
+
+            fundraisers_by_category: LookupMap::new(StorageKey::FundraisersByCategory),
+            fundraisers_by_tag: LookupMap::new(StorageKey::FundraisersByTag),
+            fundraisers_by_status: LookupMap::new(StorageKey::FundraisersByStatus),
+
+            fundraisers_sorted_by_newest: TreeMap::new(StorageKey::FundraisersSortedByNewest),
+            fundraisers_sorted_by_collected: TreeMap::new(StorageKey::FundraisersSortedByCollected),
+            fundraisers_sorted_by_remaining_goal: TreeMap::new(StorageKey::FundraisersSortedByRemainingGoal),
+            fundraisers_sorted_by_end: TreeMap::new(StorageKey::FundraisersSortedByEnd),
+
+            fundraiser_updates: LookupMap::new(StorageKey::FundraiserUpdates),
+            fundraiser_comments: LookupMap::new(StorageKey::FundraiserComments),
+
+            pledges_by_id: LookupMap::new(StorageKey::PledgesById),
+            pledges_by_due_date: TreeMap::new(StorageKey::PledgesByDueDate),
+            pledges_per_donor: LookupMap::new(StorageKey::PledgesPerDonor),
+            pledge_counter: 0,
+
+            matching_pools_by_id: LookupMap::new(StorageKey::MatchingPoolsById),
+            matching_pools_per_target: LookupMap::new(StorageKey::MatchingPoolsPerTarget),
+            matching_pool_counter: 0,
+            funding_rounds_by_id: LookupMap::new(StorageKey::FundingRoundsById),
+            funding_rounds_per_fundraiser: LookupMap::new(StorageKey::FundingRoundsPerFundraiser),
+            round_projects: LookupMap::new(StorageKey::RoundProjects),
+            funding_round_counter: 0,
+            reward_tiers: LookupMap::new(StorageKey::RewardTiers),
+            top_donor_by_fundraiser: LookupMap::new(StorageKey::TopDonorByFundraiser),
+            campaigns_supported_per_donor: LookupMap::new(StorageKey::CampaignsSupportedPerDonor),
+            voucher_keys: LookupMap::new(StorageKey::VoucherKeys),
+            claimed_vouchers: LookupSet::new(StorageKey::ClaimedVouchers),
+            campaign_token_by_fundraiser: LookupMap::new(StorageKey::CampaignTokenByFundraiser),
+            tokens_per_fundraiser: LookupMap::new(StorageKey::TokensPerFundraiser),
+            fundraiser_by_token: LookupMap::new(StorageKey::FundraiserByToken),
+            beneficiaries_by_fundraiser: LookupMap::new(StorageKey::BeneficiariesByFundraiser),
+            withdrawn_by_fundraiser: LookupMap::new(StorageKey::WithdrawnByFundraiser),
+            team_members: LookupMap::new(StorageKey::TeamMembers),
+            vesting_schedules: LookupMap::new(StorageKey::VestingSchedules),
+            donation_limits: LookupMap::new(StorageKey::DonationLimits),
+            fundraiser_allowlists: LookupMap::new(StorageKey::FundraiserAllowlists),
+            cooling_off_periods: LookupMap::new(StorageKey::CoolingOffPeriods),
+            pending_donations: LookupMap::new(StorageKey::PendingDonations),
+            pending_donations_range: LookupMap::new(StorageKey::PendingDonationsRange),
+            donations_paid_by: LookupMap::new(StorageKey::DonationsPaidBy),
         };
 
         this
@@ -108,7 +315,7 @@ impl Contract {
     // #[payable]
     // pub fn buy_nft(&mut self, zoo_id: AccountId) {
     //     let zoo_id = zoo_id.clone();
-    //     let deposit: u128 = near_sdk::env::attached_deposit();
+    //     let deposit: u128 = near_sdk::env::attached_deposit().as_yoctonear();
     //     let mut zoo: Zoo = self.zoos.get(&zoo_id).expect("Zoo doesn't exist");
     //
     //
@@ -142,7 +349,7 @@ impl Contract {
     //             reference_hash: None,
     //         });
     //
-    //     Promise::new(zoo.owner_id).transfer(deposit);
+    //     Promise::new(zoo.owner_id).transfer(NearToken::from_yoctonear(deposit));
     // }
 }
 
@@ -195,7 +402,7 @@ mod tests {
     //
     //     testing_env!(context
     //         .storage_usage(env::storage_usage())
-    //         .attached_deposit(MINT_STORAGE_COST)
+    //         .attached_deposit(NearToken::from_yoctonear(MINT_STORAGE_COST))
     //         .predecessor_account_id(accounts(0))
     //         .build());
     //
@@ -216,7 +423,7 @@ mod tests {
     //
     //     testing_env!(context
     //         .storage_usage(env::storage_usage())
-    //         .attached_deposit(MINT_STORAGE_COST)
+    //         .attached_deposit(NearToken::from_yoctonear(MINT_STORAGE_COST))
     //         .predecessor_account_id(accounts(0))
     //         .build());
     //     let token_id = "0".to_string();
@@ -224,16 +431,16 @@ mod tests {
     //
     //     testing_env!(context
     //         .storage_usage(env::storage_usage())
-    //         .attached_deposit(1)
+    //         .attached_deposit(NearToken::from_yoctonear(1))
     //         .predecessor_account_id(accounts(0))
     //         .build());
     //     contract.nft_transfer(accounts(1), token_id.clone(), None, None);
     //
     //     testing_env!(context
     //         .storage_usage(env::storage_usage())
-    //         .account_balance(env::account_balance())
+    //         .account_balance(NearToken::from_yoctonear(env::account_balance().as_yoctonear()))
     //         .is_view(true)
-    //         .attached_deposit(0)
+    //         .attached_deposit(NearToken::from_yoctonear(0))
     //         .build());
     //     if let Some(token) = contract.nft_token(token_id.clone()) {
     //         assert_eq!(token.token_id, token_id);
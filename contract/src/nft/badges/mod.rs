@@ -0,0 +1,205 @@
+// Soulbound badges: non-transferable tokens issued to donors for donation milestones.
+
+use crate::*;
+
+//numbers of supported fundraisers that earn a badge
+pub const CAMPAIGNS_SUPPORTED_MILESTONES: [u32; 2] = [10, 50];
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+#[allow(non_camel_case_types)]
+pub enum BadgeKind {
+    FIRST_DONATION,
+    CAMPAIGNS_SUPPORTED(u32),
+    TOP_DONOR(FundraiserId),
+}
+
+impl BadgeKind {
+    fn title(&self) -> String {
+        match self {
+            BadgeKind::FIRST_DONATION => "First donation".to_string(),
+            BadgeKind::CAMPAIGNS_SUPPORTED(count) => format!("{} campaigns supported", count),
+            BadgeKind::TOP_DONOR(fundraiser_id) => format!("Top donor of fundraiser #{}", fundraiser_id),
+        }
+    }
+
+    //every account can hold each kind of badge once, so the kind and the owner identify the token. The ids are reserved, nft_mint can't take them
+    fn token_id(&self, owner_id: &AccountId) -> TokenId {
+        match self {
+            BadgeKind::FIRST_DONATION => format!("badge:first_donation:{}", owner_id),
+            BadgeKind::CAMPAIGNS_SUPPORTED(count) => format!("badge:campaigns_supported_{}:{}", count, owner_id),
+            BadgeKind::TOP_DONOR(fundraiser_id) => format!("badge:top_donor_{}:{}", fundraiser_id, owner_id),
+        }
+    }
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Debug, PartialEq)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct Badge {
+    pub kind: BadgeKind,
+    //When the badge was issued, Unix epoch in milliseconds
+    pub issued_at: u64,
+}
+
+impl Badge {
+    //badges have no stored metadata, it is generated from their kind
+    pub(crate) fn metadata(&self) -> TokenMetadata {
+        TokenMetadata {
+            title: Some(self.kind.title()),
+            description: None,
+            media: None,
+            media_hash: None,
+            copies: None,
+            issued_at: Some(self.issued_at),
+            expires_at: None,
+            starts_at: None,
+            updated_at: None,
+            extra: near_sdk::serde_json::to_string(&self.kind).ok(),
+            reference: None,
+            reference_hash: None,
+        }
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    //issues the top donor badge of a fundraiser that is over, anyone can call it
+    pub fn award_top_donor_badge(&mut self, fundraiser_id: FundraiserId) -> Option<TokenId> {
+        let fundraiser: Fundraiser = self.fundraisers_by_id.get(&fundraiser_id).expect("Incorrect fundraiser id");
+        assert!(fundraiser.is_over(), "Fundraiser is not over yet");
+        assert!(fundraiser.is_visible(), "Fundraiser was hidden or taken down");
+
+        let (top_donor_id, _) = self.top_donor_by_fundraiser.get(&fundraiser_id)?;
+        self.internal_award_badge(&top_donor_id, BadgeKind::TOP_DONOR(fundraiser_id))
+    }
+
+    pub fn nft_badge_supply_for_owner(&self, account_id: AccountId) -> U128 {
+        U128(self.badges_per_owner.get(&account_id).map_or(0, |badges_set| badges_set.len()) as u128)
+    }
+
+    //badges are not listed by nft_tokens_for_owner, this is the only way to enumerate them
+    pub fn nft_badges_for_owner(&self, account_id: AccountId, from_index: Option<U128>, limit: Option<u64>) -> Vec<JsonToken> {
+        let badges_set = match self.badges_per_owner.get(&account_id) {
+            Some(badges_set) => badges_set,
+            None => return vec![],
+        };
+        badges_set.iter()
+            .skip(page_start(from_index))
+            .take(page_limit(limit))
+            .map(|token_id| self.nft_token(token_id).unwrap())
+            .collect()
+    }
+}
+
+impl Contract {
+    pub(crate) fn is_soulbound(&self, token_id: &TokenId) -> bool {
+        self.badges_by_id.get(token_id).is_some()
+    }
+
+    //mints the badge unless the account already has it, the storage is paid by the contract
+    pub(crate) fn internal_award_badge(&mut self, owner_id: &AccountId, kind: BadgeKind) -> Option<TokenId> {
+        let token_id = kind.token_id(owner_id);
+        if self.badges_by_id.get(&token_id).is_some() {
+            return None;
+        }
+        assert!(self.tokens_by_id.get(&token_id).is_none(), "Token already exists");
+
+        self.tokens_by_id.insert(&token_id, &Token { owner_id: owner_id.clone() });
+        self.badges_by_id.insert(&token_id, &Badge { kind, issued_at: env::block_timestamp_ms() });
+
+        let mut badges_set = self.badges_per_owner.get(owner_id).unwrap_or_else(|| {
+            UnorderedSet::new(
+                StorageKey::BadgesPerOwnerInner { account_id_hash: hash_account_id(owner_id) }
+            )
+        });
+        badges_set.insert(&token_id);
+        self.badges_per_owner.insert(owner_id, &badges_set);
+
+        Some(token_id)
+    }
+
+    //issues the milestone badges the donation earned and keeps track of the top donor of the fundraiser
+    pub(crate) fn internal_award_donation_badges(&mut self, fundraiser_id: FundraiserId, donor_id: &AccountId, previous_total: u128, new_total: u128) {
+        if previous_total == 0 {
+            let campaigns_supported = self.campaigns_supported_per_donor.get(donor_id).unwrap_or(0) + 1;
+            self.campaigns_supported_per_donor.insert(donor_id, &campaigns_supported);
+
+            if campaigns_supported == 1 {
+                self.internal_award_badge(donor_id, BadgeKind::FIRST_DONATION);
+            }
+            if CAMPAIGNS_SUPPORTED_MILESTONES.contains(&campaigns_supported) {
+                self.internal_award_badge(donor_id, BadgeKind::CAMPAIGNS_SUPPORTED(campaigns_supported));
+            }
+        }
+
+        let is_top_donor = self.top_donor_by_fundraiser.get(&fundraiser_id)
+            .is_none_or(|(_, top_total)| new_total > top_total);
+        if is_top_donor {
+            self.top_donor_by_fundraiser.insert(&fundraiser_id, &(donor_id.clone(), new_total));
+        }
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::accounts;
+    use near_sdk::testing_env;
+    use crate::test_helpers::{get_context, init, init_with_fundraiser, empty_token_metadata, new_fundraiser, donate, MINT_STORAGE_COST};
+
+    #[test]
+    fn first_donation_badge_test() {
+        let (mut contract, fundraiser_id) = init_with_fundraiser(accounts(0));
+        donate(&mut contract, accounts(2), fundraiser_id, 1_000 * MIN_DONATION);
+        donate(&mut contract, accounts(2), fundraiser_id, 1_000 * MIN_DONATION);
+
+        let badges = contract.nft_badges_for_owner(accounts(2), None, None);
+        assert_eq!(badges.len(), 1);
+        assert_eq!(badges[0].metadata.title, Some("First donation".to_string()));
+        //badges are kept apart from the regular tokens of the owner, but count towards the supply of the contract
+        assert_eq!(contract.nft_supply_for_owner(accounts(2)), U128(0));
+        assert_eq!(contract.nft_total_supply(), U128(2));
+        assert!(contract.nft_tokens(None, None).contains(&badges[0]));
+    }
+
+    #[test]
+    #[should_panic(expected = "Token already exists")]
+    fn badge_token_id_taken_test() {
+        let (mut contract, fundraiser_id) = init_with_fundraiser(accounts(0));
+        contract.tokens_by_id.insert(&BadgeKind::FIRST_DONATION.token_id(&accounts(2)), &Token { owner_id: accounts(3) });
+        donate(&mut contract, accounts(2), fundraiser_id, 1_000 * MIN_DONATION);
+    }
+
+    #[test]
+    fn top_donor_badge_test() {
+        let mut contract = init(accounts(0));
+        testing_env!(get_context(accounts(0))
+            .storage_usage(env::storage_usage())
+            .attached_deposit(NearToken::from_yoctonear(MINT_STORAGE_COST))
+            .build());
+        contract.add_new_fundraiser(NewFundraiser { ends_at: Some(1_000), ..new_fundraiser("test") }, empty_token_metadata());
+        let fundraiser_id = contract.fundraiser_counter;
+
+        donate(&mut contract, accounts(2), fundraiser_id, 1_000 * MIN_DONATION);
+        donate(&mut contract, accounts(3), fundraiser_id, 1_500 * MIN_DONATION);
+        donate(&mut contract, accounts(2), fundraiser_id, 1_000 * MIN_DONATION);
+
+        testing_env!(get_context(accounts(4)).block_timestamp(1_000 * 1_000_000).build());
+        let token_id = contract.award_top_donor_badge(fundraiser_id).unwrap();
+        assert_eq!(contract.nft_token(token_id).unwrap().owner_id, accounts(2));
+        //the badge is only issued once
+        assert_eq!(contract.award_top_donor_badge(fundraiser_id), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Soulbound tokens can't be transferred")]
+    fn badge_transfer_test() {
+        let (mut contract, fundraiser_id) = init_with_fundraiser(accounts(0));
+        donate(&mut contract, accounts(2), fundraiser_id, 1_000 * MIN_DONATION);
+
+        testing_env!(get_context(accounts(2)).attached_deposit(NearToken::from_yoctonear(1)).build());
+        contract.nft_transfer(accounts(3), BadgeKind::FIRST_DONATION.token_id(&accounts(2)), None);
+    }
+}
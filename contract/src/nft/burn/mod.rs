@@ -0,0 +1,121 @@
+// Contains token burning logic.
+
+use crate::*;
+
+#[near_bindgen]
+impl Contract {
+    //burns the token of the caller and refunds the storage it used. Badges can be burned too, but their storage was paid by the contract
+    #[payable]
+    pub fn nft_burn(&mut self, token_id: TokenId, memo: Option<String>) {
+        //assert that the user attached exactly 1 yoctoNEAR for security reasons
+        assert_one_yocto();
+        let owner_id = env::predecessor_account_id();
+        let token = self.tokens_by_id.get(&token_id).expect("No token");
+        assert_eq!(token.owner_id, owner_id, "Only the token owner can burn it");
+        assert!(
+            self.internal_fundraiser_of_token(&token_id).is_none(),
+            "Campaign tokens are burned by archiving their fundraiser"
+        );
+
+        let is_soulbound = self.is_soulbound(&token_id);
+        let initial_storage_usage = env::storage_usage();
+        self.internal_burn(&token_id, memo);
+        let released_storage = env::storage_byte_cost().as_yoctonear() * u128::from(initial_storage_usage - env::storage_usage());
+
+        let refund = if is_soulbound { 1 } else { released_storage + 1 };
+        Promise::new(owner_id).transfer(NearToken::from_yoctonear(refund)).detach();
+    }
+}
+
+impl Contract {
+    //the fundraiser the token is the campaign token of, if it still exists
+    pub(crate) fn internal_fundraiser_of_token(&self, token_id: &TokenId) -> Option<FundraiserId> {
+        let fundraiser_id = self.fundraiser_by_token.get(token_id)?;
+        if self.campaign_token_by_fundraiser.get(&fundraiser_id).as_ref() == Some(token_id) {
+            self.fundraisers_by_id.get(&fundraiser_id).map(|_| fundraiser_id)
+        } else {
+            None
+        }
+    }
+
+    //removes every trace of the token and emits the nft_burn event, returns the burned token
+    pub(crate) fn internal_burn(&mut self, token_id: &TokenId, memo: Option<String>) -> Token {
+        let token = self.tokens_by_id.remove(token_id).expect("No token");
+
+        if self.badges_by_id.remove(token_id).is_some() {
+            let mut badges_set = self.badges_per_owner.get(&token.owner_id).unwrap();
+            badges_set.remove(token_id);
+            if badges_set.is_empty() {
+                self.badges_per_owner.remove(&token.owner_id);
+            } else {
+                self.badges_per_owner.insert(&token.owner_id, &badges_set);
+            }
+        } else {
+            self.internal_remove_token_from_owner(&token.owner_id, token_id);
+            self.token_metadata_by_id.remove(token_id);
+            self.token_editions_by_id.remove(token_id);
+        }
+        self.internal_remove_token_from_fundraiser(token_id);
+
+        EventLog::nft(EventLogVariant::NftBurn(vec![NftBurnLog {
+            owner_id: token.owner_id.clone(),
+            token_ids: vec![token_id.clone()],
+            authorized_id: None,
+            memo,
+        }])).emit();
+
+        token
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::{accounts, get_logs};
+    use near_sdk::testing_env;
+    use crate::test_helpers::{get_context, init, init_with_fundraiser, empty_token_metadata, MINT_STORAGE_COST};
+
+    #[test]
+    fn nft_burn_test() {
+        let mut contract = init(accounts(0));
+        testing_env!(get_context(accounts(0))
+            .storage_usage(env::storage_usage())
+            .attached_deposit(NearToken::from_yoctonear(MINT_STORAGE_COST))
+            .build());
+        contract.nft_mint("receipt".to_string(), accounts(2), empty_token_metadata());
+
+        testing_env!(get_context(accounts(2)).storage_usage(env::storage_usage()).attached_deposit(NearToken::from_yoctonear(1)).build());
+        contract.nft_burn("receipt".to_string(), None);
+
+        assert!(contract.nft_token("receipt".to_string()).is_none());
+        assert_eq!(contract.nft_total_supply(), U128(0));
+        assert_eq!(contract.nft_supply_for_owner(accounts(2)), U128(0));
+        assert!(get_logs().iter().any(|log| log.contains(r#""event":"nft_burn""#)));
+    }
+
+    #[test]
+    fn nft_burn_badge_test() {
+        let (mut contract, fundraiser_id) = init_with_fundraiser(accounts(0));
+        testing_env!(get_context(accounts(2)).attached_deposit(NearToken::from_yoctonear(MIN_DONATION)).build());
+        contract.donate_to_fundraiser(fundraiser_id, None, None);
+        let token_id = contract.nft_badges_for_owner(accounts(2), None, None)[0].token_id.clone();
+
+        testing_env!(get_context(accounts(2)).storage_usage(env::storage_usage()).attached_deposit(NearToken::from_yoctonear(1)).build());
+        contract.nft_burn(token_id, None);
+        assert_eq!(contract.nft_badge_supply_for_owner(accounts(2)), U128(0));
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the token owner can burn it")]
+    fn nft_burn_not_owner_test() {
+        let mut contract = init(accounts(0));
+        testing_env!(get_context(accounts(0))
+            .storage_usage(env::storage_usage())
+            .attached_deposit(NearToken::from_yoctonear(MINT_STORAGE_COST))
+            .build());
+        contract.nft_mint("receipt".to_string(), accounts(2), empty_token_metadata());
+
+        testing_env!(get_context(accounts(3)).attached_deposit(NearToken::from_yoctonear(1)).build());
+        contract.nft_burn("receipt".to_string(), None);
+    }
+}
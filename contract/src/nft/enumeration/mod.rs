@@ -6,21 +6,22 @@ use crate::*;
 impl Contract {
     //Query for the total supply of NFTs on the contract
     pub fn nft_total_supply(&self) -> U128 {
-        //return the length of the token metadata by ID
-        U128(self.token_metadata_by_id.len() as u128)
+        //return the number of tokens with their own metadata plus the number of series editions and badges
+        U128((self.token_metadata_by_id.len() + self.token_editions_by_id.len() + self.badges_by_id.len()) as u128)
     }
 
     //Query for nft tokens on the contract regardless of the owner using pagination
     pub fn nft_tokens(&self, from_index: Option<U128>, limit: Option<u64>) -> Vec<JsonToken> {
-        //where to start pagination - if we have a from_index, we'll use that - otherwise start from 0 index
-        let start = u128::from(from_index.unwrap_or(U128(0)));
-
         //iterate through each token using an iterator
         self.token_metadata_by_id.keys()
-            //skip to the index we specified in the start variable
-            .skip(start as usize)
-            //take the first "limit" elements in the vector. If we didn't specify a limit, use 50
-            .take(limit.unwrap_or(50) as usize)
+            //followed by the editions of every series
+            .chain(self.token_editions_by_id.keys())
+            //and the badges
+            .chain(self.badges_by_id.keys())
+            //skip to the index we specified in from_index
+            .skip(page_start(from_index))
+            //take the first "limit" elements in the vector, capped by the shared pagination limits
+            .take(page_limit(limit))
             //we'll map the token IDs which are strings into Json Tokens
             .map(|token_id| self.nft_token(token_id.clone()).unwrap())
             //since we turned the keys into an iterator, we need to turn it back into a vector to return
@@ -57,15 +58,12 @@ impl Contract {
             //if there is no set of tokens, we'll simply return an empty vector.
             return vec![];
         };
-        //where to start pagination - if we have a from_index, we'll use that - otherwise start from 0 index
-        let start = u128::from(from_index.unwrap_or(U128(0)));
-
         //iterate through the keys vector
         tokens.iter()
-            //skip to the index we specified in the start variable
-            .skip(start as usize)
-            //take the first "limit" elements in the vector. If we didn't specify a limit, use 50
-            .take(limit.unwrap_or(50) as usize)
+            //skip to the index we specified in from_index
+            .skip(page_start(from_index))
+            //take the first "limit" elements in the vector, capped by the shared pagination limits
+            .take(page_limit(limit))
             //we'll map the token IDs which are strings into Json Tokens
             .map(|token_id| self.nft_token(token_id.clone()).unwrap())
             //since we turned the keys into an iterator, we need to turn it back into a vector to return
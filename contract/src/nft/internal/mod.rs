@@ -1,6 +1,11 @@
-use near_sdk::Balance;
 use crate::*;
 
+/*
+    token ids with this separator are reserved for the tokens the contract mints on its own (campaign tokens,
+    rewards, vouchers, badges and series editions), so that nobody can take one of their ids first
+*/
+pub const RESERVED_TOKEN_ID_SEPARATOR: char = ':';
+
 //used to generate a unique prefix in our storage collections (this is to avoid data collisions)
 pub(crate) fn hash_account_id(account_id: &AccountId) -> CryptoHash
 {
@@ -14,33 +19,64 @@ pub(crate) fn hash_account_id(account_id: &AccountId) -> CryptoHash
 //refund the initial deposit based on the amount of storage that was used up
 pub(crate) fn refund_deposit(storage_used: u64) {
     //get how much it would cost to store the information
-    let required_cost = env::storage_byte_cost() * Balance::from(storage_used);
+    let required_cost = env::storage_byte_cost().as_yoctonear() * u128::from(storage_used);
     //get the attached deposit
-    let attached_deposit = env::attached_deposit();
+    let attached_deposit = env::attached_deposit().as_yoctonear();
     //make sure that the attached deposit is greater than or equal to the required cost
     assert!(required_cost <= attached_deposit, "Must attach {} yoctoNEAR to cover storage", required_cost);
     //get the refund amount from the attached deposit - required cost
     let refund = attached_deposit - required_cost;
     //if the refund is greater than 1 yocto NEAR, we refund the predecessor that amount
     if refund > 1 {
-        Promise::new(env::predecessor_account_id()).transfer(refund);
+        Promise::new(env::predecessor_account_id()).transfer(NearToken::from_yoctonear(refund)).detach();
     }
 }
 
 //used to make sure the user attached exactly 1 yoctoNEAR
 pub(crate) fn assert_one_yocto() {
-    assert_eq!(env::attached_deposit(), 1, "Requires attached deposit of exactly 1 yoctoNEAR")
+    assert_eq!(env::attached_deposit().as_yoctonear(), 1, "Requires attached deposit of exactly 1 yoctoNEAR")
 }
 
 
 //Assert that the user has attached at least 1 yoctoNEAR (for security reasons and to pay for storage)
 pub(crate) fn assert_at_least_one_yocto() {
-    assert!(env::attached_deposit() >= 1, "Requires attached deposit of at least 1 yoctoNEAR")
+    assert!(env::attached_deposit().as_yoctonear() >= 1, "Requires attached deposit of at least 1 yoctoNEAR")
 }
 
 
 
 impl Contract {
+    pub(crate) fn assert_valid_token_metadata(&self, metadata: &TokenMetadata) {
+        metadata.assert_valid(self.metadata.get().unwrap().base_uri.as_ref());
+    }
+
+    //mints the token without handling the storage deposit, callers are responsible for paying for it
+    pub(crate) fn internal_mint(&mut self, token_id: TokenId, receiver_id: AccountId, mut metadata: TokenMetadata) {
+        //make sure the metadata is well formed and record when the token was minted
+        self.assert_valid_token_metadata(&metadata);
+        metadata.issued_at = Some(env::block_timestamp_ms());
+
+        //specify the token struct that contains the owner ID
+        let token = Token {
+            //set the owner ID equal to the receiver ID passed into the function
+            owner_id: receiver_id,
+        };
+        //insert the token ID and token struct and make sure that the token doesn't exist
+        assert!(self.tokens_by_id.insert(&token_id, &token).is_none(), "Token already exists");
+
+        //insert the token ID and metadata
+        self.token_metadata_by_id.insert(&token_id, &metadata);
+
+        //call the internal method for adding the token to the owner
+        self.internal_add_token_to_owner(&token.owner_id, &token_id);
+    }
+
+    //next id for a token minted by the contract, nft_mint can't mint ids in this namespace
+    pub(crate) fn internal_next_token_id(&mut self, kind: &str) -> TokenId {
+        self.contract_token_counter += 1;
+        format!("{}{}{}", kind, RESERVED_TOKEN_ID_SEPARATOR, self.contract_token_counter)
+    }
+
     pub(crate) fn internal_add_token_to_owner(&mut self, account_id: &AccountId, token_id: &TokenId) {
         //get the set of tokens for the given account
         let mut tokens_set = self.tokens_per_owner.get(account_id).unwrap_or_else(|| {
@@ -49,8 +85,6 @@ impl Contract {
                 StorageKey::TokenPerOwnerInner {
                     account_id_hash: hash_account_id(&account_id)
                 }
-                    .try_to_vec()
-                    .unwrap()
             )
         });
 
@@ -83,6 +117,8 @@ impl Contract {
                                     memo: Option<String>) -> Token {
         //get the token object by passing in the token_id
         let token = self.tokens_by_id.get(&token_id).expect("No token");
+        //soulbound tokens stay with the account they were issued to
+        assert!(!self.is_soulbound(token_id), "Soulbound tokens can't be transferred");
         //if the sender doesn't equal the owner, we panic
         if sender_id != &token.owner_id {
             env::panic_str("Unauthorized");
@@ -12,6 +12,7 @@ pub struct Payout {
 }
 
 #[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[borsh(crate = "near_sdk::borsh")]
 #[serde(crate = "near_sdk::serde")]
 pub struct NFTContractMetadata {
     // required, essentially a version like "nft-1.0.0"
@@ -31,6 +32,7 @@ pub struct NFTContractMetadata {
 }
 
 #[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, PartialEq, Debug)]
+#[borsh(crate = "near_sdk::borsh")]
 #[serde(crate = "near_sdk::serde")]
 pub struct TokenMetadata {
     pub title: Option<String>, // ex. "Arch Nemesis: Mail Carrier" or "Parcel #5055"
@@ -47,7 +49,82 @@ pub struct TokenMetadata {
     pub reference_hash: Option<Base64VecU8>, // Base64-encoded sha256 hash of JSON from reference field. Required if `reference` is included.
 }
 
+const MAX_TOKEN_TITLE_LENGTH: usize = 200;
+const MAX_TOKEN_DESCRIPTION_LENGTH: usize = 2000;
+const MAX_TOKEN_EXTRA_LENGTH: usize = 2000;
+const MAX_URL_LENGTH: usize = 500;
+const MAX_ICON_LENGTH: usize = 10_000;
+//schemes accepted for media, anything without a scheme is resolved against the base_uri of the contract
+const MEDIA_URL_SCHEMES: [&str; 3] = ["ipfs://", "ar://", "https://"];
+//sha256 hashes
+const HASH_LENGTH: usize = 32;
+
+//panics unless the hash is set exactly when its field is and is a sha256 hash
+fn assert_valid_hash(field: &Option<String>, hash: &Option<Base64VecU8>, field_name: &str) {
+    match (field, hash) {
+        (Some(_), None) => panic!("Abort. {} hash is required when {} is set", field_name, field_name.to_lowercase()),
+        (None, Some(_)) => panic!("Abort. {} hash is set without {}", field_name, field_name.to_lowercase()),
+        (_, Some(hash)) => assert!(hash.0.len() == HASH_LENGTH, "Abort. {} hash must be 32 bytes", field_name),
+        (None, None) => {}
+    }
+}
+
+impl NFTContractMetadata {
+    pub(crate) fn assert_valid(&self) {
+        assert!(!self.spec.is_empty() && self.spec.chars().count() <= 32, "Abort. Spec must be between 1 and 32 characters");
+        assert!(!self.name.is_empty() && self.name.chars().count() <= MAX_TOKEN_TITLE_LENGTH, "Abort. Name must be between 1 and 200 characters");
+        assert!(!self.symbol.is_empty() && self.symbol.chars().count() <= 32, "Abort. Symbol must be between 1 and 32 characters");
+        if let Some(icon) = &self.icon {
+            assert!(icon.starts_with("data:"), "Abort. Icon must be a data URL");
+            assert!(icon.len() <= MAX_ICON_LENGTH, "Abort. Icon is longer then 10000 characters");
+        }
+        if let Some(base_uri) = &self.base_uri {
+            assert!(base_uri.len() <= MAX_URL_LENGTH, "Abort. Base URI is longer then 500 characters");
+        }
+        if let Some(reference) = &self.reference {
+            assert!(reference.len() <= MAX_URL_LENGTH, "Abort. Reference is longer then 500 characters");
+        }
+        assert_valid_hash(&self.reference, &self.reference_hash, "Reference");
+    }
+}
+
+impl TokenMetadata {
+    //`base_uri` is the one of the contract, relative media are only allowed when it is set
+    pub(crate) fn assert_valid(&self, base_uri: Option<&String>) {
+        if let Some(title) = &self.title {
+            assert!(title.len() <= MAX_TOKEN_TITLE_LENGTH, "Abort. Token title is longer then 200 characters");
+        }
+        if let Some(description) = &self.description {
+            assert!(description.len() <= MAX_TOKEN_DESCRIPTION_LENGTH, "Abort. Token description is longer then 2000 characters");
+        }
+        if let Some(extra) = &self.extra {
+            assert!(extra.len() <= MAX_TOKEN_EXTRA_LENGTH, "Abort. Token extra is longer then 2000 characters");
+        }
+        if let Some(media) = &self.media {
+            assert!(media.len() <= MAX_URL_LENGTH, "Abort. Media is longer then 500 characters");
+            if media.contains("://") {
+                assert!(
+                    MEDIA_URL_SCHEMES.iter().any(|scheme| media.starts_with(scheme)),
+                    "Abort. Media must be an ipfs://, ar:// or https:// URL"
+                );
+            } else {
+                assert!(base_uri.is_some(), "Abort. Relative media requires a base URI in the contract metadata");
+            }
+        }
+        assert_valid_hash(&self.media, &self.media_hash, "Media");
+        if let Some(reference) = &self.reference {
+            assert!(reference.len() <= MAX_URL_LENGTH, "Abort. Reference is longer then 500 characters");
+        }
+        assert_valid_hash(&self.reference, &self.reference_hash, "Reference");
+        assert!(self.copies != Some(0), "Abort. Copies must be greater than 0");
+        if let (Some(starts_at), Some(expires_at)) = (self.starts_at, self.expires_at) {
+            assert!(starts_at < expires_at, "Abort. Token must start before it expires");
+        }
+    }
+}
+
 #[derive(BorshDeserialize, BorshSerialize, Debug, PartialEq, Deserialize,Serialize)]
+#[borsh(crate = "near_sdk::borsh")]
 #[serde(crate = "near_sdk::serde")]
 pub struct Token {
     //owner of the token
@@ -55,7 +132,7 @@ pub struct Token {
 }
 
 //The Json token is what will be returned from view calls.
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
 #[serde(crate = "near_sdk::serde")]
 pub struct JsonToken {
     //token ID
@@ -77,3 +154,88 @@ impl NonFungibleTokenMetadata for Contract {
         self.metadata.get().unwrap()
     }
 }
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::accounts;
+    use near_sdk::testing_env;
+    use crate::test_helpers::{get_context, init, empty_token_metadata, MINT_STORAGE_COST};
+
+    fn with_media(media: &str, media_hash: Vec<u8>) -> TokenMetadata {
+        TokenMetadata {
+            media: Some(media.to_string()),
+            media_hash: Some(Base64VecU8(media_hash)),
+            ..empty_token_metadata()
+        }
+    }
+
+    #[test]
+    fn valid_token_metadata_test() {
+        with_media("ipfs://bafybeiekkhg57mp7u22zwiwjqxiwe3r4birpm2xwlrgv7u6boxndwgpi3y", vec![0; 32]).assert_valid(None);
+        with_media("bafybeiekkhg57mp7u22zwiwjqxiwe3r4birpm2xwlrgv7u6boxndwgpi3y", vec![0; 32]).assert_valid(Some(&"https://ipfs.io/ipfs/".to_string()));
+    }
+
+    #[test]
+    #[should_panic(expected = "Abort. Media hash is required when media is set")]
+    fn media_without_hash_test() {
+        TokenMetadata { media: Some("https://example.com/image.png".to_string()), ..empty_token_metadata() }.assert_valid(None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Abort. Media must be an ipfs://, ar:// or https:// URL")]
+    fn media_scheme_test() {
+        with_media("http://example.com/image.png", vec![0; 32]).assert_valid(None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Abort. Media hash must be 32 bytes")]
+    fn media_hash_length_test() {
+        with_media("https://example.com/image.png", vec![0; 16]).assert_valid(None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Abort. Token must start before it expires")]
+    fn token_validity_period_test() {
+        TokenMetadata { starts_at: Some(2_000), expires_at: Some(1_000), ..empty_token_metadata() }.assert_valid(None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Abort. Icon must be a data URL")]
+    fn contract_icon_test() {
+        NFTContractMetadata {
+            spec: "nft-1.0.0".to_string(),
+            name: "Ukraine Fundraisers".to_string(),
+            symbol: "UFUND".to_string(),
+            icon: Some("https://example.com/icon.svg".to_string()),
+            base_uri: None,
+            reference: None,
+            reference_hash: None,
+        }.assert_valid();
+    }
+
+    #[test]
+    fn contract_symbol_length_in_characters_test() {
+        NFTContractMetadata {
+            spec: "nft-1.0.0".to_string(),
+            name: "Ukraine Fundraisers".to_string(),
+            symbol: "Ї".repeat(32),
+            icon: None,
+            base_uri: None,
+            reference: None,
+            reference_hash: None,
+        }.assert_valid();
+    }
+
+    #[test]
+    fn issued_at_filled_on_mint_test() {
+        let mut contract = init(accounts(0));
+        testing_env!(get_context(accounts(0))
+            .storage_usage(env::storage_usage())
+            .attached_deposit(NearToken::from_yoctonear(MINT_STORAGE_COST))
+            .block_timestamp(5_000 * 1_000_000)
+            .build());
+        contract.nft_mint("1".to_string(), accounts(2), TokenMetadata { issued_at: Some(1), ..empty_token_metadata() });
+        assert_eq!(contract.nft_token("1".to_string()).unwrap().metadata.issued_at, Some(5_000));
+    }
+}
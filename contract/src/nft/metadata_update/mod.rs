@@ -0,0 +1,145 @@
+// Updates of the contract metadata and of the metadata of campaign tokens.
+
+use crate::*;
+
+//metadata a token had before one of its updates
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Debug, PartialEq)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct TokenMetadataRevision {
+    // position of the revision in the log of the token, starting from 0
+    pub id: u64,
+    pub metadata: TokenMetadata,
+    pub replaced_by: AccountId,
+    // When the metadata was replaced, Unix epoch in milliseconds
+    pub replaced_at: u64,
+}
+
+#[near_bindgen]
+impl Contract {
+    pub fn update_contract_metadata(&mut self, metadata: NFTContractMetadata) {
+        self.assert_contract_owner();
+        metadata.assert_valid();
+        self.metadata.set(&metadata);
+    }
+
+    /*
+        replaces the metadata of a campaign token, only the fundraiser owner and its team can call it.
+        The previous metadata is kept in the revision log of the token, the caller pays for its storage.
+    */
+    #[payable]
+    pub fn update_token_metadata(&mut self, token_id: TokenId, metadata: TokenMetadata) -> u64 {
+        let fundraiser_id = self.internal_fundraiser_of_token(&token_id).expect("Only campaign tokens can be updated");
+        let fundraiser: Fundraiser = self.fundraisers_by_id.get(&fundraiser_id).unwrap();
+        let caller_id = env::predecessor_account_id();
+        self.assert_fundraiser_permission(fundraiser_id, &fundraiser, TeamPermission::EDIT_CONTENT, "update the token metadata");
+        self.assert_valid_token_metadata(&metadata);
+
+        let initial_storage_usage = env::storage_usage();
+        let previous = self.token_metadata_by_id.get(&token_id).expect("No token metadata");
+        let now = env::block_timestamp_ms();
+        let metadata = TokenMetadata {
+            //the mint date can't be rewritten
+            issued_at: previous.issued_at,
+            updated_at: Some(now),
+            ..metadata
+        };
+        self.token_metadata_by_id.insert(&token_id, &metadata);
+
+        let mut revisions = self.token_metadata_revisions.get(&token_id).unwrap_or_else(|| {
+            Vector::new(
+                StorageKey::TokenMetadataRevisionsInner { token_id_hash: env::sha256_array(token_id.as_bytes()) }
+            )
+        });
+        let revision_id = revisions.len();
+        revisions.push(&TokenMetadataRevision {
+            id: revision_id,
+            metadata: previous,
+            replaced_by: caller_id.clone(),
+            replaced_at: now,
+        });
+        self.token_metadata_revisions.insert(&token_id, &revisions);
+
+        refund_deposit(env::storage_usage().saturating_sub(initial_storage_usage));
+
+        EventLog::new(EventLogVariant::TokenMetadataUpdate(vec![TokenMetadataUpdateLog {
+            token_id,
+            fundraiser_id,
+            revision_id,
+            updated_by: caller_id,
+        }])).emit();
+
+        revision_id
+    }
+
+    //previous metadata of the token, newest first
+    pub fn nft_token_metadata_revisions(&self, token_id: TokenId, cursor: Option<String>, limit: Option<u64>) -> Page<TokenMetadataRevision> {
+        if let Some(revisions) = self.token_metadata_revisions.get(&token_id) {
+            paginate_newest_first(&revisions, cursor, limit, |_| true)
+        } else {
+            Page { items: vec![], next_cursor: None }
+        }
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::{accounts, get_logs};
+    use near_sdk::testing_env;
+    use crate::test_helpers::{get_context, init, init_with_fundraiser, empty_token_metadata, STORAGE_DEPOSIT};
+
+    fn update(contract: &mut Contract, caller_id: AccountId, token_id: TokenId, title: &str) -> u64 {
+        testing_env!(get_context(caller_id)
+            .storage_usage(env::storage_usage())
+            .attached_deposit(NearToken::from_yoctonear(STORAGE_DEPOSIT))
+            .block_timestamp(7_000 * 1_000_000)
+            .build());
+        contract.update_token_metadata(token_id, TokenMetadata { title: Some(title.to_string()), ..empty_token_metadata() })
+    }
+
+    #[test]
+    fn update_token_metadata_test() {
+        let (mut contract, fundraiser_id) = init_with_fundraiser(accounts(0));
+        let token_id = contract.campaign_token_by_fundraiser.get(&fundraiser_id).unwrap();
+        update(&mut contract, accounts(0), token_id.clone(), "first");
+        assert_eq!(update(&mut contract, accounts(0), token_id.clone(), "second"), 1);
+
+        let metadata = contract.nft_token(token_id.clone()).unwrap().metadata;
+        assert_eq!(metadata.title, Some("second".to_string()));
+        assert_eq!(metadata.updated_at, Some(7_000));
+        assert!(get_logs().iter().any(|log| log.contains(r#""event":"token_metadata_update""#)));
+
+        let revisions = contract.nft_token_metadata_revisions(token_id, None, None).items;
+        assert_eq!(revisions.len(), 2);
+        assert_eq!(revisions[0].metadata.title, Some("first".to_string()));
+        assert_eq!(revisions[1].metadata.title, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the fundraiser owner or team members with the EDIT_CONTENT permission can update the token metadata")]
+    fn update_token_metadata_not_owner_test() {
+        let (mut contract, fundraiser_id) = init_with_fundraiser(accounts(0));
+        let token_id = contract.campaign_token_by_fundraiser.get(&fundraiser_id).unwrap();
+        update(&mut contract, accounts(2), token_id, "first");
+    }
+
+    #[test]
+    fn update_contract_metadata_test() {
+        let mut contract = init(accounts(0));
+        let mut metadata = contract.nft_metadata();
+        metadata.icon = Some("data:image/svg+xml,<svg></svg>".to_string());
+        contract.update_contract_metadata(metadata);
+        assert_eq!(contract.nft_metadata().icon, Some("data:image/svg+xml,<svg></svg>".to_string()));
+        assert_eq!(contract.nft_metadata().name, "Ukraine Fundraisers".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the contract owner can call this method")]
+    fn update_contract_metadata_not_owner_test() {
+        let mut contract = init(accounts(0));
+        let metadata = contract.nft_metadata();
+        testing_env!(get_context(accounts(2)).build());
+        contract.update_contract_metadata(metadata);
+    }
+}
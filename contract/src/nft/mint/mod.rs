@@ -1,6 +1,27 @@
 // Contains token minting logic.
 
 use crate::*;
+use near_sdk::Gas;
+
+//gas kept for minting one more token of a batch
+const GAS_FOR_BATCH_MINT_TOKEN: Gas = Gas::from_tgas(5);
+//gas kept for the storage refund at the end of a batch
+const GAS_FOR_BATCH_MINT_REFUND: Gas = Gas::from_tgas(10);
+
+//result of a batch mint, the leftover tokens can be sent again in another call
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct BatchMintResult {
+    pub minted: Vec<TokenId>,
+    pub leftover: Vec<TokenId>,
+}
+
+fn assert_user_token_id(token_id: &TokenId) {
+    assert!(
+        !token_id.contains(RESERVED_TOKEN_ID_SEPARATOR),
+        "Abort. Token ids with a '{}' are reserved for the tokens minted by the contract", RESERVED_TOKEN_ID_SEPARATOR
+    );
+}
 
 #[near_bindgen]
 impl Contract {
@@ -11,22 +32,12 @@ impl Contract {
         receiver_id: AccountId,
         metadata: TokenMetadata,
     ) {
+        assert_user_token_id(&token_id);
         //measure the initial storage being used on the contract
         let initial_storage_usage = env::storage_usage();
 
-        //specify the token struct that contains the owner ID
-        let token = Token {
-            //set the owner ID equal to the receiver ID passed into the function
-            owner_id: receiver_id,
-        };
-        //insert the token ID and token struct and make sure that the token doesn't exist
-        assert!(self.tokens_by_id.insert(&token_id, &token).is_none(), "Token already exists");
-
-        //insert the token ID and metadata
-        self.token_metadata_by_id.insert(&token_id, &metadata);
-
-        //call the internal method for adding the token to the owner
-        self.internal_add_token_to_owner(&token.owner_id, &token_id);
+        //mint the token, panics if it already exists
+        self.internal_mint(token_id, receiver_id, metadata);
 
         //calculate the required storage which was the used - initial
         let required_storage_in_bytes = env::storage_usage() - initial_storage_usage;
@@ -37,4 +48,100 @@ impl Contract {
 
         // self.token.internal_mint(token_id, receiver_id, Some(token_metadata))
     }
-}
\ No newline at end of file
+
+    /*
+        mints the tokens in order, with one storage calculation and refund for the whole batch.
+        Minting stops when the gas left is too low to mint another token, the tokens that were not
+        minted are returned as leftover.
+    */
+    #[payable]
+    pub fn nft_batch_mint(&mut self, tokens: Vec<(TokenId, AccountId, TokenMetadata)>) -> BatchMintResult {
+        //measure the initial storage being used on the contract
+        let initial_storage_usage = env::storage_usage();
+
+        let mut minted: Vec<TokenId> = Vec::with_capacity(tokens.len());
+        let mut leftover: Vec<TokenId> = vec![];
+        for (token_id, _, _) in tokens.iter() {
+            assert_user_token_id(token_id);
+        }
+        for (token_id, receiver_id, metadata) in tokens {
+            let gas_left = env::prepaid_gas().saturating_sub(env::used_gas());
+            if !leftover.is_empty() || gas_left < GAS_FOR_BATCH_MINT_TOKEN.saturating_add(GAS_FOR_BATCH_MINT_REFUND) {
+                leftover.push(token_id);
+                continue;
+            }
+            self.internal_mint(token_id.clone(), receiver_id, metadata);
+            minted.push(token_id);
+        }
+
+        //refund any excess storage, the deposit must cover every token that was minted
+        refund_deposit(env::storage_usage() - initial_storage_usage);
+
+        BatchMintResult { minted, leftover }
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::accounts;
+    use near_sdk::testing_env;
+    use crate::test_helpers::{get_context, init, empty_token_metadata, MINT_STORAGE_COST};
+
+    fn batch(count: usize) -> Vec<(TokenId, AccountId, TokenMetadata)> {
+        (0..count).map(|index| (format!("receipt-{}", index), accounts(index % 5), empty_token_metadata())).collect()
+    }
+
+    #[test]
+    fn nft_batch_mint_test() {
+        let mut contract = init(accounts(0));
+        testing_env!(get_context(accounts(0))
+            .storage_usage(env::storage_usage())
+            .attached_deposit(NearToken::from_yoctonear(5 * MINT_STORAGE_COST))
+            .build());
+        let result = contract.nft_batch_mint(batch(5));
+
+        assert_eq!(result.minted.len(), 5);
+        assert!(result.leftover.is_empty());
+        assert_eq!(contract.nft_total_supply(), U128(5));
+        assert_eq!(contract.nft_token("receipt-3".to_string()).unwrap().owner_id, accounts(3));
+    }
+
+    #[test]
+    fn nft_batch_mint_leftover_test() {
+        let mut contract = init(accounts(0));
+        testing_env!(get_context(accounts(0))
+            .storage_usage(env::storage_usage())
+            .attached_deposit(NearToken::from_yoctonear(5 * MINT_STORAGE_COST))
+            .prepaid_gas(GAS_FOR_BATCH_MINT_REFUND)
+            .build());
+        let result = contract.nft_batch_mint(batch(5));
+
+        //not enough gas to mint anything, every token is left over in order
+        assert!(result.minted.is_empty());
+        assert_eq!(result.leftover, batch(5).into_iter().map(|(token_id, _, _)| token_id).collect::<Vec<_>>());
+        assert_eq!(contract.nft_total_supply(), U128(0));
+    }
+
+    #[test]
+    #[should_panic(expected = "Abort. Token ids with a ':' are reserved for the tokens minted by the contract")]
+    fn nft_mint_reserved_token_id_test() {
+        let mut contract = init(accounts(0));
+        testing_env!(get_context(accounts(2))
+            .storage_usage(env::storage_usage())
+            .attached_deposit(NearToken::from_yoctonear(MINT_STORAGE_COST))
+            .build());
+        contract.nft_mint("reward:1".to_string(), accounts(2), empty_token_metadata());
+    }
+
+    #[test]
+    #[should_panic(expected = "Must attach")]
+    fn nft_batch_mint_storage_test() {
+        let mut contract = init(accounts(0));
+        testing_env!(get_context(accounts(0))
+            .storage_usage(env::storage_usage())
+            .attached_deposit(NearToken::from_yoctonear(MINT_STORAGE_COST))
+            .build());
+        contract.nft_batch_mint(batch(5));
+    }
+}
@@ -2,10 +2,17 @@ pub use metadata::*;
 pub use internal::*;
 pub use enumeration::*;
 pub use nft_core::*;
+pub use series::*;
+pub use badges::*;
+pub use metadata_update::*;
 
 mod mint;
 mod enumeration;
 mod metadata;
 mod nft_core;
 mod royalty;
-mod internal;
\ No newline at end of file
+mod internal;
+mod series;
+mod badges;
+mod burn;
+mod metadata_update;
\ No newline at end of file
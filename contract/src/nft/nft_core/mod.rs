@@ -3,8 +3,8 @@
 use crate::*;
 use near_sdk::{ext_contract, Gas, log, PromiseResult};
 
-const GAS_FOR_RESOLVE_TRANSFER: Gas = Gas(10_000_000_000_000);
-const GAS_FOR_NFT_ON_TRANSFER: Gas = Gas(25_000_000_000_000);
+const GAS_FOR_RESOLVE_TRANSFER: Gas = Gas::from_tgas(10);
+const GAS_FOR_NFT_ON_TRANSFER: Gas = Gas::from_tgas(25);
 
 pub trait NonFungibleTokenCore {
     //transfers an NFT to a receiver ID
@@ -40,7 +40,7 @@ impl NonFungibleTokenCore for Contract {
     //get the information for a specific token ID
     fn nft_token(&self, token_id: TokenId) -> Option<JsonToken> {
         if let Some(token) = self.tokens_by_id.get(&token_id) {
-            let metadata = self.token_metadata_by_id.get(&token_id).unwrap();
+            let metadata = self.internal_token_metadata(&token_id);
             Some(JsonToken {
                 token_id,
                 owner_id: token.owner_id,
@@ -0,0 +1,196 @@
+// Token series: many editions sharing one metadata template, so every mint only stores a reference to it.
+
+use crate::*;
+
+pub type SeriesId = u64;
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Debug, PartialEq)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct TokenSeries {
+    //the only account allowed to mint editions of the series
+    pub creator_id: AccountId,
+    //metadata shared by every edition
+    pub metadata: TokenMetadata,
+    //max number of editions, unlimited if not set
+    pub max_supply: Option<u64>,
+    //number of editions minted so far
+    pub minted: u64,
+}
+
+//what is stored for a token of a series instead of its metadata
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Debug, PartialEq)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct TokenEdition {
+    pub series_id: SeriesId,
+    //number of the edition, starting from 1
+    pub edition: u64,
+    //When the edition was minted, Unix epoch in milliseconds
+    pub issued_at: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct JsonTokenSeries {
+    pub series_id: SeriesId,
+    pub series: TokenSeries,
+}
+
+//token IDs of series editions look like "<series_id>:<edition>"
+pub(crate) fn edition_token_id(series_id: SeriesId, edition: u64) -> TokenId {
+    format!("{}:{}", series_id, edition)
+}
+
+#[near_bindgen]
+impl Contract {
+    //creates a series, the creator pays for the storage of the template
+    #[payable]
+    pub fn nft_create_series(&mut self, metadata: TokenMetadata, max_supply: Option<u64>) -> SeriesId {
+        if let Some(max_supply) = max_supply {
+            assert!(max_supply > 0, "Abort. Max supply must be greater than 0");
+        }
+        self.assert_valid_token_metadata(&metadata);
+        let initial_storage_usage = env::storage_usage();
+
+        self.series_counter += 1;
+        let series_id: SeriesId = self.series_counter;
+        self.token_series_by_id.insert(&series_id, &TokenSeries {
+            creator_id: env::predecessor_account_id(),
+            metadata,
+            max_supply,
+            minted: 0,
+        });
+
+        refund_deposit(env::storage_usage() - initial_storage_usage);
+
+        series_id
+    }
+
+    //mints the next edition of the series to the receiver, only the creator of the series can call it
+    #[payable]
+    pub fn nft_mint_series(&mut self, series_id: SeriesId, receiver_id: AccountId) -> TokenId {
+        let initial_storage_usage = env::storage_usage();
+
+        let token_id = self.internal_mint_edition(series_id, receiver_id);
+
+        refund_deposit(env::storage_usage() - initial_storage_usage);
+
+        token_id
+    }
+
+    pub fn nft_series(&self, series_id: SeriesId) -> Option<JsonTokenSeries> {
+        self.token_series_by_id.get(&series_id).map(|series| JsonTokenSeries { series_id, series })
+    }
+
+    //editions of the series, in the order they were minted
+    pub fn nft_tokens_for_series(&self, series_id: SeriesId, from_index: Option<U128>, limit: Option<u64>) -> Vec<JsonToken> {
+        let series = match self.token_series_by_id.get(&series_id) {
+            Some(series) => series,
+            None => return vec![],
+        };
+        (1..=series.minted)
+            .filter_map(|edition| self.nft_token(edition_token_id(series_id, edition)))
+            .skip(page_start(from_index))
+            .take(page_limit(limit))
+            .collect()
+    }
+}
+
+impl Contract {
+    pub(crate) fn internal_mint_edition(&mut self, series_id: SeriesId, receiver_id: AccountId) -> TokenId {
+        let mut series = self.token_series_by_id.get(&series_id).expect("Incorrect series id");
+        assert_eq!(env::predecessor_account_id(), series.creator_id, "Only the series creator can mint editions");
+        if let Some(max_supply) = series.max_supply {
+            assert!(series.minted < max_supply, "Series mint cap reached");
+        }
+
+        series.minted += 1;
+        let token_id = edition_token_id(series_id, series.minted);
+        let token = Token { owner_id: receiver_id };
+        assert!(self.tokens_by_id.insert(&token_id, &token).is_none(), "Token already exists");
+        self.token_editions_by_id.insert(&token_id, &TokenEdition {
+            series_id,
+            edition: series.minted,
+            issued_at: env::block_timestamp_ms(),
+        });
+        self.internal_add_token_to_owner(&token.owner_id, &token_id);
+        self.token_series_by_id.insert(&series_id, &series);
+
+        token_id
+    }
+
+    //metadata of the token, merged from the template of its series for editions and generated for badges
+    pub(crate) fn internal_token_metadata(&self, token_id: &TokenId) -> TokenMetadata {
+        if let Some(metadata) = self.token_metadata_by_id.get(token_id) {
+            return metadata;
+        }
+        if let Some(badge) = self.badges_by_id.get(token_id) {
+            return badge.metadata();
+        }
+        let edition = self.token_editions_by_id.get(token_id).expect("No token metadata");
+        let series = self.token_series_by_id.get(&edition.series_id).unwrap();
+        let template = series.metadata;
+        TokenMetadata {
+            title: template.title.map(|title| format!("{} #{}", title, edition.edition)),
+            copies: series.max_supply.or(template.copies),
+            issued_at: Some(edition.issued_at),
+            ..template
+        }
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::accounts;
+    use near_sdk::testing_env;
+    use crate::test_helpers::{get_context, init, empty_token_metadata, STORAGE_DEPOSIT};
+
+    fn create_series(contract: &mut Contract, max_supply: Option<u64>) -> SeriesId {
+        testing_env!(get_context(accounts(0))
+            .storage_usage(env::storage_usage())
+            .attached_deposit(NearToken::from_yoctonear(STORAGE_DEPOSIT))
+            .build());
+        contract.nft_create_series(TokenMetadata {
+            title: Some("Donation receipt".to_string()),
+            ..empty_token_metadata()
+        }, max_supply)
+    }
+
+    fn mint(contract: &mut Contract, series_id: SeriesId, receiver_id: AccountId) -> TokenId {
+        testing_env!(get_context(accounts(0))
+            .storage_usage(env::storage_usage())
+            .attached_deposit(NearToken::from_yoctonear(STORAGE_DEPOSIT))
+            .build());
+        contract.nft_mint_series(series_id, receiver_id)
+    }
+
+    #[test]
+    fn series_token_metadata_test() {
+        let mut contract = init(accounts(0));
+        let series_id = create_series(&mut contract, Some(2));
+
+        let token_id = mint(&mut contract, series_id, accounts(2));
+        assert_eq!(token_id, "1:1".to_string());
+        let token = contract.nft_token(token_id).unwrap();
+        assert_eq!(token.owner_id, accounts(2));
+        assert_eq!(token.metadata.title, Some("Donation receipt #1".to_string()));
+        assert_eq!(token.metadata.copies, Some(2));
+
+        mint(&mut contract, series_id, accounts(3));
+        assert_eq!(contract.nft_series(series_id).unwrap().series.minted, 2);
+        assert_eq!(contract.nft_total_supply(), U128(2));
+        assert_eq!(contract.nft_tokens(None, None).len(), 2);
+        assert_eq!(contract.nft_tokens_for_series(series_id, Some(U128(1)), None)[0].owner_id, accounts(3));
+    }
+
+    #[test]
+    #[should_panic(expected = "Series mint cap reached")]
+    fn series_mint_cap_test() {
+        let mut contract = init(accounts(0));
+        let series_id = create_series(&mut contract, Some(1));
+        mint(&mut contract, series_id, accounts(2));
+        mint(&mut contract, series_id, accounts(3));
+    }
+}
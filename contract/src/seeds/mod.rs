@@ -13,21 +13,30 @@ impl Contract {
 }
 fn fundraiser_seed(contract: &mut Contract) {
     let nft_media: String = String::from("https://bafybeiekkhg57mp7u22zwiwjqxiwe3r4birpm2xwlrgv7u6boxndwgpi3y.ipfs.nftstorage.link/");
-    contract.add_new_fundraiser("Test".to_string(),
-                                "test".to_string(),
-                                FundraiserStatus::ACTIVE,
-                                TokenMetadata {
-                                    title: Some(String::from("NFT title")),
-                                    description: Some(String::from("NFT description")),
-                                    media: Some(nft_media),
-                                    media_hash: None,
-                                    copies: None,
-                                    issued_at: None,
-                                    expires_at: None,
-                                    starts_at: None,
-                                    updated_at: None,
-                                    extra: None,
-                                    reference: None,
-                                    reference_hash: None
-                                });
+    contract.add_new_fundraiser(
+        NewFundraiser {
+            title: "Test".to_string(),
+            description: "test".to_string(),
+            status: FundraiserStatus::ACTIVE,
+            category: FundraiserCategory::HUMANITARIAN,
+            tags: vec!["test".to_string()],
+            goal: Some(U128(100_000_000_000_000_000_000_000_000)),
+            ends_at: None,
+        },
+        TokenMetadata {
+            title: Some(String::from("NFT title")),
+            description: Some(String::from("NFT description")),
+            media: Some(nft_media),
+            //dev placeholder, not the real hash of the image
+            media_hash: Some(Base64VecU8(vec![0; 32])),
+            copies: None,
+            issued_at: None,
+            expires_at: None,
+            starts_at: None,
+            updated_at: None,
+            extra: None,
+            reference: None,
+            reference_hash: None
+        },
+    );
 }
\ No newline at end of file
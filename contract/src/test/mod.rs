@@ -4,6 +4,10 @@ pub mod test_helpers {
     use near_sdk::testing_env;
     use crate::*;
 
+    pub const MINT_STORAGE_COST: u128 = 6000000000000000000000;
+    pub const ONE_NEAR: u128 = 1_000_000_000_000_000_000_000_000;
+    //0.01 NEAR, more than enough for the storage of the records added by a single call
+    pub const STORAGE_DEPOSIT: u128 = 10_000_000_000_000_000_000_000;
 
     pub fn get_context(predecessor_account_id: AccountId) -> VMContextBuilder {
         let mut builder = VMContextBuilder::new();
@@ -20,4 +24,52 @@ pub mod test_helpers {
         let contract = Contract::new_default_meta(account_id);
         contract
     }
+
+    pub fn empty_token_metadata() -> TokenMetadata {
+        TokenMetadata {
+            title: None,
+            description: None,
+            media: None,
+            media_hash: None,
+            copies: None,
+            issued_at: None,
+            expires_at: None,
+            starts_at: None,
+            updated_at: None,
+            extra: None,
+            reference: None,
+            reference_hash: None,
+        }
+    }
+
+    //an active fundraiser without a category, tags, goal or end date
+    pub fn new_fundraiser(title: &str) -> NewFundraiser {
+        NewFundraiser {
+            title: title.to_string(),
+            description: "".to_string(),
+            status: FundraiserStatus::ACTIVE,
+            category: FundraiserCategory::OTHER,
+            tags: vec![],
+            goal: None,
+            ends_at: None,
+        }
+    }
+
+    //donates `amount` yoctoNEAR to the fundraiser from `donor_id`
+    pub fn donate(contract: &mut Contract, donor_id: AccountId, fundraiser_id: FundraiserId, amount: u128) {
+        testing_env!(get_context(donor_id).attached_deposit(NearToken::from_yoctonear(amount)).build());
+        contract.donate_to_fundraiser(fundraiser_id, None, None);
+    }
+
+    //initializes the contract and creates one active fundraiser owned by `account_id`
+    pub fn init_with_fundraiser(account_id: AccountId) -> (Contract, FundraiserId) {
+        let mut contract = init(account_id.clone());
+        testing_env!(get_context(account_id)
+            .storage_usage(env::storage_usage())
+            .attached_deposit(NearToken::from_yoctonear(MINT_STORAGE_COST))
+            .build());
+        contract.add_new_fundraiser(new_fundraiser("test"), empty_token_metadata());
+        let fundraiser_id = contract.fundraiser_counter;
+        (contract, fundraiser_id)
+    }
 }
\ No newline at end of file